@@ -0,0 +1,167 @@
+use soroban_sdk::{panic_with_error, Address, Env, Vec};
+
+use crate::{
+    glyphs::MAX_PAGE_SIZE,
+    storage::persistent::{
+        read_color, read_color_allowance, read_color_burned, read_color_inventory, read_color_inventory_length,
+        read_color_inventory_position, read_color_total, remove_color_inventory, remove_color_inventory_position,
+        write_color, write_color_allowance, write_color_burned, write_color_inventory, write_color_inventory_length,
+        write_color_total,
+    },
+    types::{Allowance, Error},
+};
+
+/* NOTE
+`Color(miner, owner, color)` balances can't be listed by owner so we keep a `(miner, color)` inventory per owner alongside them
+It's one `ColorInventory(owner, position)` entry per balance so being sent lots of tiny balances can't grow any single entry past its size limit
+    Each balance remembers its position so emptying one swaps the last entry into the gap, `colors_of` order isn't mining order
+    `ColorTotal(owner, color)` sums the indexed balances of a color over every miner
+An inventory entry exists for as long as its balance is non-zero
+Balances from before the inventory existed get indexed the first time they're credited or debited, or by anyone calling `color_index`
+*/
+
+pub fn color_credit(env: &Env, miner: &Address, owner: &Address, color: u32, amount: u32) {
+    if amount == 0 {
+        return;
+    }
+
+    let current_amount = color_indexed(env, miner, owner, color);
+
+    if current_amount == 0 {
+        inventory_add(env, miner, owner, color);
+    }
+
+    write_color_total(env, owner.clone(), color, read_color_total(env, owner.clone(), color) + amount);
+    write_color(env, miner.clone(), owner.clone(), color, current_amount + amount);
+}
+
+pub fn color_debit(env: &Env, miner: &Address, owner: &Address, color: u32, amount: u32) {
+    let current_amount = color_indexed(env, miner, owner, color);
+
+    if amount > current_amount {
+        panic_with_error!(env, Error::InsufficientColors);
+    }
+
+    if amount == current_amount {
+        inventory_remove(env, miner, owner, color);
+    }
+
+    write_color_total(env, owner.clone(), color, read_color_total(env, owner.clone(), color) - amount);
+    write_color(env, miner.clone(), owner.clone(), color, current_amount - amount);
+}
+
+// Backfills the inventory for a balance from before it existed, a no-op for empty or already listed balances
+pub fn color_index(env: &Env, miner: &Address, owner: &Address, color: u32) {
+    color_indexed(env, miner, owner, color);
+}
+
+// The balance, indexing it first if it's a non-zero balance the inventory doesn't know about yet
+fn color_indexed(env: &Env, miner: &Address, owner: &Address, color: u32) -> u32 {
+    let amount = read_color(env, miner.clone(), owner.clone(), color);
+
+    if amount > 0 && read_color_inventory_position(env, owner.clone(), miner.clone(), color).is_none() {
+        inventory_add(env, miner, owner, color);
+        write_color_total(env, owner.clone(), color, read_color_total(env, owner.clone(), color) + amount);
+    }
+
+    amount
+}
+
+fn inventory_add(env: &Env, miner: &Address, owner: &Address, color: u32) {
+    let length = read_color_inventory_length(env, owner.clone());
+
+    write_color_inventory(env, owner.clone(), length, miner.clone(), color);
+    write_color_inventory_length(env, owner.clone(), length + 1);
+}
+
+fn inventory_remove(env: &Env, miner: &Address, owner: &Address, color: u32) {
+    let Some(position) = read_color_inventory_position(env, owner.clone(), miner.clone(), color) else {
+        return;
+    };
+
+    let last = read_color_inventory_length(env, owner.clone()) - 1;
+
+    if position != last {
+        let (last_miner, last_color) =
+            read_color_inventory(env, owner.clone(), last).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+        write_color_inventory(env, owner.clone(), position, last_miner, last_color);
+    }
+
+    remove_color_inventory(env, owner.clone(), last);
+    remove_color_inventory_position(env, owner.clone(), miner.clone(), color);
+    write_color_inventory_length(env, owner.clone(), last);
+}
+
+pub fn colors_of(env: &Env, owner: &Address, cursor: u32, limit: u32) -> Vec<(Address, u32, u32)> {
+    let end = cursor
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(read_color_inventory_length(env, owner.clone()));
+    let mut colors = Vec::new(env);
+
+    for position in cursor..end {
+        if let Some((miner, color)) = read_color_inventory(env, owner.clone(), position) {
+            let amount = read_color(env, miner.clone(), owner.clone(), color);
+
+            colors.push_back((miner, color, amount));
+        }
+    }
+
+    colors
+}
+
+// Debit everything before crediting anything so a self transfer nets out to nothing
+pub fn colors_move(env: &Env, from: &Address, to: &Address, colors: &Vec<(Address, u32, u32)>) {
+    for (miner, color, amount) in colors.iter() {
+        color_debit(env, &miner, from, color, amount);
+    }
+
+    for (miner, color, amount) in colors.iter() {
+        color_credit(env, &miner, to, color, amount);
+    }
+}
+
+pub fn colors_burn(env: &Env, owner: &Address, colors: &Vec<(Address, u32, u32)>) {
+    for (miner, color, amount) in colors.iter() {
+        color_debit(env, &miner, owner, color, amount);
+
+        let burned = read_color_burned(env, color);
+
+        write_color_burned(env, color, burned + amount as u64);
+    }
+}
+
+pub fn color_allowance(env: &Env, owner: &Address, spender: &Address, miner: &Address, color: u32) -> u32 {
+    match read_color_allowance(env, owner.clone(), spender.clone(), miner.clone(), color) {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance.amount,
+        _ => 0,
+    }
+}
+
+pub fn color_spend_allowance(
+    env: &Env,
+    owner: &Address,
+    spender: &Address,
+    miner: &Address,
+    color: u32,
+    amount: u32,
+) {
+    let allowance = read_color_allowance(env, owner.clone(), spender.clone(), miner.clone(), color)
+        .unwrap_or_else(|| panic_with_error!(env, Error::NotAuthorized));
+
+    if allowance.expiration_ledger < env.ledger().sequence() || amount > allowance.amount {
+        panic_with_error!(env, Error::NotAuthorized);
+    }
+
+    write_color_allowance(
+        env,
+        owner.clone(),
+        spender.clone(),
+        miner.clone(),
+        color,
+        &Allowance {
+            amount: allowance.amount - amount,
+            expiration_ledger: allowance.expiration_ledger,
+        },
+    );
+}
@@ -1,10 +1,18 @@
-use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, BytesN, Env, Map, Vec};
+use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Bytes, BytesN, Env, Map, String, Vec};
 
 use crate::{
-    glyphs::{glyph_store, glyph_verify_ownership}, interface::{ColorGlyphTrait, ColorsInterface, Exchange, GlyphInterface}, offers::{offer_delete, offer_post, offers_get}, storage::{instance::*, persistent::{has_colors, read_color, read_colors_or_error, read_colors_or_map, read_glyph, remove_colors, remove_glyph_offer, remove_glyph_owner, write_color, write_colors}}, types::{Error, GlyphType, HashType, Offer, StorageKey}
+    colors::{color_allowance, color_credit, color_index, color_spend_allowance, colors_burn, colors_move, colors_of}, events::{ColorsApproveEvent, ColorsBurnEvent, ColorsInEvent, ColorsMineEvent, ColorsTransferEvent, GlyphApproveAllEvent, GlyphApproveEvent, GlyphMetadataEvent, GlyphScrapeCompleteEvent, GlyphScrapeEvent, GlyphTransferEvent}, glyphs::{colors_verify_ownership, draft_clear, draft_erase, draft_next, draft_recover_legacy, glyph_is_approved, glyph_is_operator, glyph_index, glyph_mint, glyph_preview, glyph_scrape_progress, glyph_set_owner, glyph_spans_expand, glyph_uri, glyph_verify_ownership, glyphs_of, MAX_BASE_URI_SIZE}, history::{glyph_history, glyph_history_add}, interface::{ColorGlyphTrait, ColorsInterface, Exchange, GlyphInterface, NonFungible}, metadata::{glyph_metadata_remove, glyph_metadata_set_policy, glyph_metadata_verify, glyph_metadata_verify_editor}, offers::{offer_delete, offer_post, offers_get}, render::{glyph_data_uri, glyph_render}, pricing::{burn_refund, mine_cost_add, mine_price_verify, mine_prices, mine_quote, mine_supply_add, price_curve_verify}, storage::{instance::*, persistent::{read_color, read_color_burned, read_color_supply, read_draft_or_error, read_draft_or_map, read_drafts, read_color_total, read_glyph, read_glyph_approval, read_glyph_metadata, read_glyph_metadata_policy, read_glyph_minter, read_glyph_owner, read_glyph_transparent, read_owner_glyph_count, remove_draft, remove_glyph_approval, remove_glyph_operator, write_color_allowance, write_draft, write_glyph_approval, write_glyph_metadata, write_glyph_operator}}, types::{Allowance, Approval, Canvas, Error, GlyphMetadata, GlyphPreview, GlyphType, HashType, MetadataPolicy, MintOptions, Offer, PixelSpan, PriceCurve, Provenance, ProvenanceEntry, RemintPolicy, RenderFormat, ScrapeProgress, StorageKey}
 };
 
-pub const MAX_BIT24_SIZE: usize = 40 * 40 * 3 + 1;
+// Hard ceiling on the canvas that sizes all the fixed buffers, the owner's `Canvas` setting has to fit inside it
+pub const MAX_PIXELS: usize = 4096;
+pub const MAX_BIT24_SIZE: usize = MAX_PIXELS * 3 + MAX_PIXELS / 8 + 1 + 2 + 6; // rgb + transparency mask + padding + u32 width + u16 version
+
+pub const DEFAULT_MAX_WIDTH: u32 = 40;
+pub const DEFAULT_MAX_HEIGHT: u32 = 40;
+
+pub const NAME: &str = "Colorglyph";
+pub const SYMBOL: &str = "GLYPH";
 
 #[contract]
 pub struct ColorGlyph;
@@ -15,7 +23,7 @@ impl ColorGlyphTrait for ColorGlyph {
         owner_address.require_auth();
 
         if env.storage().instance().has(&StorageKey::OwnerAddress) {
-            panic_with_error!(env, Error::NotEmpty);
+            panic_with_error!(env, Error::AlreadyInitialized);
         }
 
         let max_entry_lifetime: u32 = 12 * 60 * 24 * 31 - 1; // A year's worth of ledgers - 12
@@ -53,6 +61,11 @@ impl ColorGlyphTrait for ColorGlyph {
             write_owner_address(&env, &owner)
         }
         if let Some(address) = token_address {
+            // The treasury is held in the current token, switching would pay refunds out of offer escrow
+            if address != read_token_address(&env) && read_treasury_balance(&env) > 0 {
+                panic_with_error!(&env, Error::NotEmpty);
+            }
+
             write_token_address(&env, &address);
         }
         if let Some(address) = fee_address {
@@ -64,12 +77,133 @@ impl ColorGlyphTrait for ColorGlyph {
         if let Some(count) = max_payment_count {
             write_max_payment_count(&env, &count);
         }
-        if let Some(rate) = minter_royalty_rate {
-            write_minter_royalty_rate(&env, &rate);
+        if minter_royalty_rate.is_some() || miner_royalty_rate.is_some() {
+            let minter_royalty_rate = minter_royalty_rate.unwrap_or_else(|| read_minter_royalty_rate(&env));
+            let miner_royalty_rate = miner_royalty_rate.unwrap_or_else(|| read_miner_royalty_rate(&env));
+
+            // Royalties come out of the sale amount so together they can't take more than all of it
+            if minter_royalty_rate < 0 || miner_royalty_rate < 0 || minter_royalty_rate + miner_royalty_rate > 100 {
+                panic_with_error!(&env, Error::RoyaltyOverflow);
+            }
+
+            write_minter_royalty_rate(&env, &minter_royalty_rate);
+            write_miner_royalty_rate(&env, &miner_royalty_rate);
+        }
+    }
+
+    fn update_base_uri(env: Env, base_uri: String) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if base_uri.len() as usize > MAX_BASE_URI_SIZE {
+            panic_with_error!(env, Error::UriTooLong);
+        }
+
+        write_base_uri(&env, &base_uri);
+    }
+
+    fn update_mine_price(
+        env: Env,
+        base_price: Option<i128>,
+        color_multipliers: Option<Map<u32, u32>>,
+        volume_discounts: Option<Vec<(u32, u32)>>,
+    ) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        let mut mine_price = read_mine_price(&env);
+
+        if let Some(price) = base_price {
+            mine_price.base_price = price;
+        }
+        if let Some(multipliers) = color_multipliers {
+            mine_price.color_multipliers = multipliers;
+        }
+        if let Some(discounts) = volume_discounts {
+            mine_price.volume_discounts = discounts;
+        }
+
+        mine_price_verify(&env, &mine_price);
+        write_mine_price(&env, &mine_price);
+    }
+
+    fn update_price_curve(env: Env, price_curve: PriceCurve) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        price_curve_verify(&env, &price_curve);
+        write_price_curve(&env, &price_curve);
+    }
+
+    fn update_burn_refund_rate(env: Env, refund_rate: i128) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if !(0..=100).contains(&refund_rate) {
+            panic_with_error!(&env, Error::InvalidRate);
+        }
+
+        write_burn_refund_rate(&env, &refund_rate);
+    }
+
+    // The treasury is kept apart from offer escrow, it's only ever funded here and only ever pays out burn refunds
+    fn treasury_deposit(env: Env, source: Address, amount: i128) {
+        source.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
         }
-        if let Some(rate) = miner_royalty_rate {
-            write_miner_royalty_rate(&env, &rate);
-        }        
+
+        let token_address = read_token_address(&env);
+        let token = token::Client::new(&env, &token_address);
+
+        token.transfer(&source, &env.current_contract_address(), &amount);
+
+        write_treasury_balance(&env, &(read_treasury_balance(&env) + amount));
+    }
+
+    fn treasury_withdraw(env: Env, to: Address, amount: i128) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        let balance = read_treasury_balance(&env);
+
+        if amount <= 0 || amount > balance {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        let token_address = read_token_address(&env);
+        let token = token::Client::new(&env, &token_address);
+
+        token.transfer(&env.current_contract_address(), &to, &amount);
+
+        write_treasury_balance(&env, &(balance - amount));
+    }
+
+    fn treasury_balance(env: Env) -> i128 {
+        read_treasury_balance(&env)
+    }
+
+    fn update_canvas(env: Env, max_width: u32, max_height: u32) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        if max_width == 0 || max_height == 0 || max_width as u64 * max_height as u64 > MAX_PIXELS as u64 {
+            panic_with_error!(&env, Error::InvalidCanvas);
+        }
+
+        write_canvas(&env, &Canvas { max_width, max_height });
+    }
+
+    fn update_remint_policy(env: Env, policy: RemintPolicy) {
+        let owner = read_owner_address(&env);
+        owner.require_auth();
+
+        write_remint_policy(&env, &policy);
+    }
+
+    fn remint_policy(env: Env) -> RemintPolicy {
+        read_remint_policy(&env)
     }
 
     fn upgrade(env: Env, hash: BytesN<32>) {
@@ -89,48 +223,171 @@ impl ColorsInterface for ColorGlyph {
         colors: Map<u32, u32>,
         miner: Option<Address>,
         to: Option<Address>,
+        max_total_price: Option<i128>,
     ) {
         source.require_auth();
 
         let miner = miner.unwrap_or(source.clone());
         let to = to.unwrap_or(source.clone());
         
-        let mut pay_amount: u32 = 0;
+        let (pay_amount, color_prices) = mine_prices(&env, &colors);
 
-        for (color, amount) in colors.iter() {
-            let current_amount = read_color(&env, miner.clone(), to.clone(), color);
-            write_color(&env, miner.clone(), to.clone(), color, current_amount + amount);
-
-            pay_amount += amount;
+        if max_total_price.is_some_and(|max_total_price| pay_amount > max_total_price) {
+            panic_with_error!(&env, Error::MaxPriceExceeded);
         }
 
-        crate::events::colors_mine(&env, &miner, &to, colors);
+        mine_supply_add(&env, &colors);
+        mine_cost_add(&env, &miner, &colors, &color_prices);
+
+        for (color, amount) in colors.iter() {
+            color_credit(&env, &miner, &to, color, amount);
+        }
 
         let token_address = read_token_address(&env);
         let fee_address = read_fee_address(&env);
         
         let token = token::Client::new(&env, &token_address);
 
-        // TODO this is just a stroop fee so not sufficient. This will need to be adjusted before going live
-        token.transfer(&source, &fee_address, &(pay_amount as i128));
+        token.transfer(&source, &fee_address, &pay_amount);
+
+        crate::events::colors_mine(
+            &env,
+            ColorsMineEvent {
+                source,
+                miner,
+                to,
+                colors,
+                price: pay_amount,
+            },
+        );
+    }
+
+    fn colors_quote(env: Env, colors: Map<u32, u32>) -> i128 {
+        mine_quote(&env, &colors)
+    }
+
+    fn color_supply(env: Env, color: u32) -> u64 {
+        read_color_supply(&env, color)
     }
 
     fn colors_transfer(env: Env, from: Address, to: Address, colors: Vec<(Address, u32, u32)>) {
         from.require_auth();
 
-        for (miner, color, amount) in colors.iter() {
-            let current_from_amount = read_color(&env, miner.clone(), from.clone(), color);
-            let current_to_amount = read_color(&env, miner.clone(), to.clone(), color);
-            
-            if amount > current_from_amount {
-                panic_with_error!(env, Error::NotPermitted);
-            }
+        colors_move(&env, &from, &to, &colors);
+
+        crate::events::colors_transfer(
+            &env,
+            ColorsTransferEvent {
+                spender: from.clone(),
+                from,
+                to,
+                colors,
+            },
+        );
+    }
+
+    fn colors_burn(env: Env, owner: Address, colors: Vec<(Address, u32, u32)>) -> i128 {
+        owner.require_auth();
+
+        colors_burn(&env, &owner, &colors);
+
+        // Refunds stop once the treasury runs dry
+        let treasury_balance = read_treasury_balance(&env);
+        let refund = burn_refund(&env, &colors).min(treasury_balance);
+
+        if refund > 0 {
+            let token_address = read_token_address(&env);
+            let token = token::Client::new(&env, &token_address);
+
+            token.transfer(&env.current_contract_address(), &owner, &refund);
+
+            write_treasury_balance(&env, &(treasury_balance - refund));
+        }
+
+        crate::events::colors_burn(
+            &env,
+            ColorsBurnEvent {
+                owner,
+                colors,
+                refund,
+            },
+        );
+
+        refund
+    }
+
+    fn color_burned(env: Env, color: u32) -> u64 {
+        read_color_burned(&env, color)
+    }
 
-            write_color(&env, miner.clone(), from.clone(), color, current_from_amount - amount);
-            write_color(&env, miner.clone(), to.clone(), color, current_to_amount + amount);
+    fn colors_approve(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+        amount: u32,
+        expiration_ledger: u32,
+    ) {
+        owner.require_auth();
+
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic_with_error!(env, Error::InvalidExpiration);
+        }
+
+        write_color_allowance(
+            &env,
+            owner.clone(),
+            spender.clone(),
+            miner.clone(),
+            color,
+            &Allowance {
+                amount,
+                expiration_ledger,
+            },
+        );
+
+        crate::events::colors_approve(
+            &env,
+            ColorsApproveEvent {
+                owner,
+                spender,
+                miner,
+                color,
+                amount,
+                expiration_ledger,
+            },
+        );
+    }
+
+    fn colors_allowance(env: Env, owner: Address, spender: Address, miner: Address, color: u32) -> u32 {
+        color_allowance(&env, &owner, &spender, &miner, color)
+    }
+
+    fn colors_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        colors: Vec<(Address, u32, u32)>,
+    ) {
+        spender.require_auth();
+
+        for (miner, color, amount) in colors.iter() {
+            color_spend_allowance(&env, &from, &spender, &miner, color, amount);
         }
 
-        crate::events::colors_transfer(&env, &from, &to, colors);
+        colors_move(&env, &from, &to, &colors);
+
+        crate::events::colors_transfer(
+            &env,
+            ColorsTransferEvent {
+                spender,
+                from,
+                to,
+                colors,
+            },
+        );
     }
 
     fn color_balance(env: Env, owner: Address, color: u32, miner: Option<Address>) -> u32 {
@@ -138,6 +395,18 @@ impl ColorsInterface for ColorGlyph {
         
         read_color(&env, miner, owner, color)
     }
+
+    fn colors_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<(Address, u32, u32)> {
+        colors_of(&env, &owner, cursor, limit)
+    }
+
+    fn color_total(env: Env, owner: Address, color: u32) -> u32 {
+        read_color_total(&env, owner, color)
+    }
+
+    fn color_index(env: Env, owner: Address, miner: Address, color: u32) {
+        color_index(&env, &miner, &owner, color)
+    }
 }
 
 #[contractimpl]
@@ -148,158 +417,185 @@ impl GlyphInterface for ColorGlyph {
         to: Option<Address>,
         colors: Map<Address, Map<u32, Vec<u32>>>,
         width: Option<u32>,
+        options: Option<MintOptions>,
     ) -> Option<BytesN<32>> {
-        let mut glyph_colors = read_colors_or_map(&env, minter.clone());
-
-        // spend colors
-        for (miner, color_indexes) in colors.iter() {
-            let mut skip = false;
-
-            for (color, indexes) in color_indexes.iter() {
-//                let current_color_key = StorageKey::Color(miner.clone(), minter.clone(), color);
-                let current_color_amount = read_color(&env, miner.clone(), minter.clone(), color);
-                write_color(&env, miner.clone(), minter.clone(), color, current_color_amount - indexes.len());
-
-                
-                crate::events::colors_out(&env, &miner, &minter, color, indexes.len());
-
-                if !skip {
-                    match glyph_colors.get(miner.clone()) {
-                        Some(result) => match result {
-                            mut color_indexes_ => match color_indexes_.get(color) {
-                                // Existing miner and color
-                                Some(result) => match result {
-                                    mut indexes_ => {
-                                        indexes_.append(&indexes);
-                                        color_indexes_.set(color, indexes_);
-                                        glyph_colors.set(miner.clone(), color_indexes_);
-                                    }
-                                },
-                                // Existing miner no color
-                                None => {
-                                    color_indexes_.set(color, indexes);
-                                    glyph_colors.set(miner.clone(), color_indexes_);
-                                }
-                            },
-                        },
-                        // No miner (or no exisiting Colors)
-                        None => {
-                            glyph_colors.set(miner.clone(), color_indexes.clone());
-                            // We set a skip vs using break to ensure we continue to bill for the spent colors
-                            skip = true; // we need to break here otherwise we continue looping inside this nested color loop which we've already fully added
-                        }
-                    }
-                }
-            }
-        }
+        minter.require_auth();
 
-        match width {
-            // We are storing the glyph
-            Some(width) => {
-                let hash = glyph_store(&env, minter.clone(), to.clone(), glyph_colors, width as u8);
+        glyph_mint(&env, minter, to, colors, width, options.unwrap_or_default())
+    }
+    fn glyph_mint_spans(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<PixelSpan>>>,
+        width: Option<u32>,
+        options: Option<MintOptions>,
+    ) -> Option<BytesN<32>> {
+        minter.require_auth();
 
-                crate::events::minted_event(&env, &minter, to, &hash);
+        let colors = glyph_spans_expand(&env, &colors, width);
 
-                Some(hash)
-            }
-            // We are building the glyph
-            None => {
-                write_colors(&env, minter.clone(), &glyph_colors);
-                crate::events::minting_event(&env, &minter);
-                
-                None
-            }
-        }
+        glyph_mint(&env, minter, to, colors, width, options.unwrap_or_default())
     }
-    fn glyph_transfer(env: Env, to: Address, hash_type: HashType) {
-        match hash_type {
-            HashType::Colors(from) => {
-                from.require_auth();
+    fn draft_paint(env: Env, minter: Address, draft: u32, colors: Map<Address, Map<u32, Vec<u32>>>) {
+        minter.require_auth();
+
+        // Refund whatever's being painted over so the indexes are free again
+        draft_clear(&env, &minter, draft, &colors);
+
+        glyph_mint(
+            &env,
+            minter,
+            None,
+            colors,
+            None,
+            MintOptions {
+                draft: Some(draft),
+                ..Default::default()
+            },
+        );
+    }
+    fn draft_erase(env: Env, minter: Address, draft: u32, colors: Map<Address, Map<u32, Vec<u32>>>) {
+        minter.require_auth();
+
+        draft_erase(&env, &minter, draft, &colors);
+    }
+    fn glyph_transfer(env: Env, to: Address, hash_type: HashType, spender: Option<Address>) {
+        let (from, received) = match &hash_type {
+            HashType::Colors(from, draft) => {
+                colors_verify_ownership(&env, from, &spender);
+
+                // Every draft is another entry in `to`'s list so nobody gets handed ones they didn't ask for
+                if to != *from {
+                    to.require_auth();
+                }
     
-                let from_colors_key = StorageKey::Colors(from.clone());
-                let colors = read_colors_or_error(&env, from.clone());
+                let colors = read_draft_or_error(&env, from.clone(), *draft);
     
-                env.storage().persistent().remove(&from_colors_key);
-                write_colors(&env, to.clone(), &colors);
-                
-                crate::events::transfer_colors_event(&env, &from, &to);
+                remove_draft(&env, from.clone(), *draft);
+
+                // Land in a fresh draft so we never merge into something `to` is already building
+                let to_draft = draft_next(&env, &to);
+
+                write_draft(&env, to.clone(), to_draft, &colors);
+
+                (from.clone(), HashType::Colors(to.clone(), to_draft))
             }
             HashType::Glyph(glyph_hash) => {
-                let glyph_owner_key = StorageKey::GlyphOwner(glyph_hash.clone());
-    
-                glyph_verify_ownership(&env, &glyph_owner_key);
-    
-                env.storage().persistent().set(&glyph_owner_key, &to);
+                let owner = glyph_verify_ownership(&env, glyph_hash, &spender);
     
-                
-                crate::events::transfer_glyph_event(&env, &to, &glyph_hash);
+                glyph_set_owner(&env, glyph_hash, Some(owner.clone()), Some(to.clone()));
+                glyph_history_add(&env, glyph_hash, Provenance::Transfer(owner.clone(), to.clone()));
+
+                (owner, hash_type.clone())
             }
-        }
+        };
+
+        crate::events::glyph_transfer(
+            &env,
+            GlyphTransferEvent {
+                hash_type,
+                spender: spender.unwrap_or(from.clone()),
+                from,
+                to,
+                received,
+            },
+        );
     }
-    fn glyph_scrape(env: Env, to: Option<Address>, hash_type: HashType) {
-        let mut miners_colors_indexes: Map<Address, Map<u32, Vec<u32>>>;
+    fn glyph_scrape(
+        env: Env,
+        to: Option<Address>,
+        hash_type: HashType,
+        spender: Option<Address>,
+        limit: Option<u32>,
+    ) -> ScrapeProgress {
+        let max_payment_count = read_max_payment_count(&env);
+
+        // Callers can take smaller bites but never more than the instance allows
+        let limit = match limit {
+            Some(0) => panic_with_error!(&env, Error::InvalidAmount),
+            Some(limit) => limit.min(max_payment_count),
+            None => max_payment_count,
+        };
 
-        let owner: Address = match &hash_type {
-            HashType::Colors(colors_owner) => {
-                colors_owner.require_auth();
-                miners_colors_indexes = read_colors_or_error(&env, colors_owner.clone());
+        let mut miners_colors_indexes: Map<Address, Map<u32, Vec<u32>>>;
 
-                crate::events::scrape_colors_event(&env, colors_owner, to.clone());
+        let (owner, draft) = match &hash_type {
+            HashType::Colors(colors_owner, draft) => {
+                colors_verify_ownership(&env, colors_owner, &spender);
+                miners_colors_indexes = read_draft_or_error(&env, colors_owner.clone(), *draft);
 
-                colors_owner.clone()
+                (colors_owner.clone(), *draft)
             }
             HashType::Glyph(glyph_hash) => {
-                let owner_key = StorageKey::GlyphOwner(glyph_hash.clone());
-                let owner = glyph_verify_ownership(&env, &owner_key);
+                let owner = glyph_verify_ownership(&env, glyph_hash, &spender);
 
-                // Ensure we don't start a scrape while there's a pending mint, otherwise we'll overwrite the pending with the new
-                // We use the Address vs the BytesN<32> as the key in order to maintain ownership of the Colors
-                // If we wanted to support scraping multiple glyphs at once we'd need to track ownership another way
-                
-                if has_colors(&env, owner.clone())
-                {
-                    panic_with_error!(env, Error::NotEmpty);
+                let glyph = read_glyph(&env, glyph_hash.clone()).unwrap_or_else(|e| panic_with_error!(&env, e));
+
+                // A spender approved for just this glyph has no say over the owner's drafts, so they can't leave a remainder to continue
+                if let Some(spender) = &spender {
+                    if *spender != owner
+                        && !glyph_is_operator(&env, &owner, spender)
+                        && glyph_scrape_progress(0, &glyph.colors).colors > limit
+                    {
+                        panic_with_error!(&env, Error::NotAuthorized);
+                    }
                 }
 
-                let glyph = read_glyph(&env, glyph_hash.clone()).unwrap_or_else(|e| panic_with_error!(&env, e));
+                // Whatever this call doesn't move waits in a draft of its own so it can't mix with anything pending
+                // We use the Address vs the BytesN<32> as the key in order to maintain ownership of the Colors
+                let draft = draft_next(&env, &owner);
 
                 // Remove glyph owner
-                remove_glyph_owner(&env, glyph_hash.clone());
-                
-                // Remove all glyph sell offers
-                remove_glyph_offer(&env, glyph_hash.clone());
+                glyph_set_owner(&env, glyph_hash, Some(owner.clone()), None);
+                glyph_history_add(&env, glyph_hash, Provenance::Scrape(owner.clone()));
+
+                glyph_metadata_remove(&env, glyph_hash);
 
                 miners_colors_indexes = glyph.colors;
-                crate::events::scrape_glyph_event(&env, &owner, to.clone(), glyph_hash);
-                
-                owner
+
+                (owner, draft)
             }
         };
 
         // loop through the glyph colors and send them to `to`
-        let mut payment_count: u8 = 0;
+        let mut payment_count: u32 = 0;
         let to_address = to.unwrap_or(owner.clone());
 
-        let max_payment_count = read_max_payment_count(&env) as u8;
+        crate::events::glyph_scrape(
+            &env,
+            GlyphScrapeEvent {
+                hash_type: hash_type.clone(),
+                owner: owner.clone(),
+                to: to_address.clone(),
+                draft,
+            },
+        );
+
+        let mut credited: Map<Address, Map<u32, u32>> = Map::new(&env);
 
         for (miner, mut colors_indexes) in miners_colors_indexes.iter() {
-            if payment_count >= max_payment_count {
+            if payment_count >= limit {
                 break;
             }
 
+            let mut miner_credited: Map<u32, u32> = Map::new(&env);
+
             for (color, indexes) in colors_indexes.iter() {
-                // TODO do we need to dupe this line with the above?
-                if payment_count >= max_payment_count {
+                // The outer check saves walking the rest of the miners once we're full
+                if payment_count >= limit {
                     break;
                 }
 
-                let current_amount = read_color(&env, miner.clone(), to_address.clone(), color);
-                write_color(&env, miner.clone(), to_address.clone(), color, current_amount + indexes.len());
+                color_credit(&env, &miner, &to_address, color, indexes.len());
 
                 colors_indexes.remove(color);
+                miner_credited.set(color, indexes.len());
                 payment_count += 1;
+            }
 
-                crate::events::color_in_event(&env, &miner, &to_address, color, indexes.len());
+            if !miner_credited.is_empty() {
+                credited.set(miner.clone(), miner_credited);
             }
 
             if colors_indexes.is_empty() {
@@ -309,17 +605,45 @@ impl GlyphInterface for ColorGlyph {
             }
         }
 
+        crate::events::colors_in(
+            &env,
+            ColorsInEvent {
+                owner: owner.clone(),
+                to: to_address.clone(),
+                colors: credited,
+            },
+        );
+
         if miners_colors_indexes.is_empty() {
-            remove_colors(&env, owner)
-            //env.storage().persistent().remove(&colors_key);
+            remove_draft(&env, owner.clone(), draft);
+
+            crate::events::glyph_scrape_complete(
+                &env,
+                GlyphScrapeCompleteEvent {
+                    owner,
+                    draft,
+                    to: to_address,
+                },
+            );
         } else {
-            write_colors(&env, owner, &miners_colors_indexes);
+            write_draft(&env, owner, draft, &miners_colors_indexes);
         }
+
+        glyph_scrape_progress(draft, &miners_colors_indexes)
+    }
+    fn glyph_scrape_status(env: Env, owner: Address, draft: u32) -> ScrapeProgress {
+        glyph_scrape_progress(draft, &read_draft_or_map(&env, owner, draft))
+    }
+    fn drafts_of(env: Env, owner: Address) -> Vec<u32> {
+        read_drafts(&env, owner)
+    }
+    fn draft_recover_legacy(env: Env, owner: Address) -> u32 {
+        draft_recover_legacy(&env, &owner)
     }
     fn glyph_get(env: Env, hash_type: HashType) -> Result<GlyphType, Error> {
         match hash_type {
-            HashType::Colors(address) => {
-                let colors = read_colors_or_error(&env, address);
+            HashType::Colors(address, draft) => {
+                let colors = read_draft_or_error(&env, address, draft);
                 Ok(GlyphType::Colors(colors))
             }
             HashType::Glyph(hash) => {
@@ -334,15 +658,223 @@ impl GlyphInterface for ColorGlyph {
             }
         }
     }
+    fn glyph_preview(
+        env: Env,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+        transparent: bool,
+    ) -> GlyphPreview {
+        glyph_preview(&env, &colors, width, transparent)
+    }
+    fn glyph_minter(env: Env, hash: BytesN<32>) -> Option<Address> {
+        read_glyph_minter(&env, hash)
+    }
+    fn glyph_history(env: Env, hash: BytesN<32>, cursor: u32) -> Vec<ProvenanceEntry> {
+        glyph_history(&env, &hash, cursor)
+    }
+    fn glyph_transparent(env: Env, hash: BytesN<32>) -> bool {
+        read_glyph_transparent(&env, hash)
+    }
+    fn glyph_canvas(env: Env) -> Canvas {
+        read_canvas(&env)
+    }
+    fn glyph_render(env: Env, hash: BytesN<32>, format: RenderFormat) -> Bytes {
+        Self::owner_of(env.clone(), hash.clone());
+
+        let glyph = read_glyph(&env, hash.clone()).unwrap_or_else(|e| panic_with_error!(&env, e));
+
+        glyph_render(&env, &glyph, read_glyph_transparent(&env, hash), format)
+    }
+    fn glyphs_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<BytesN<32>> {
+        glyphs_of(&env, &owner, cursor, limit)
+    }
+    fn glyph_count(env: Env, owner: Address) -> u32 {
+        read_owner_glyph_count(&env, owner)
+    }
+    fn glyph_index(env: Env, hash: BytesN<32>) {
+        glyph_index(&env, &hash)
+    }
+    fn glyph_metadata(env: Env, hash: BytesN<32>) -> Option<GlyphMetadata> {
+        read_glyph_metadata(&env, hash)
+    }
+    fn glyph_metadata_policy(env: Env, hash: BytesN<32>) -> MetadataPolicy {
+        read_glyph_metadata_policy(&env, hash)
+    }
+    fn glyph_metadata_update(env: Env, hash: BytesN<32>, metadata: GlyphMetadata, spender: Option<Address>) {
+        let editor = glyph_metadata_verify_editor(&env, &hash, &spender);
+
+        glyph_metadata_verify(&env, &metadata);
+        write_glyph_metadata(&env, hash.clone(), &metadata);
+
+        crate::events::metadata_updated(
+            &env,
+            GlyphMetadataEvent {
+                hash,
+                editor,
+                metadata,
+            },
+        );
+    }
+    fn glyph_metadata_set_policy(env: Env, hash: BytesN<32>, policy: MetadataPolicy, spender: Option<Address>) {
+        glyph_metadata_set_policy(&env, &hash, &policy, &spender);
+    }
+}
+
+#[contractimpl]
+impl NonFungible for ColorGlyph {
+    fn owner_of(env: Env, token_id: BytesN<32>) -> Address {
+        read_glyph_owner(&env, token_id).unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+    }
+    fn balance(env: Env, owner: Address) -> u32 {
+        read_owner_glyph_count(&env, owner)
+    }
+    fn transfer(env: Env, from: Address, to: Address, token_id: BytesN<32>) {
+        from.require_auth();
+
+        let owner = Self::owner_of(env.clone(), token_id.clone());
+
+        if owner != from {
+            panic_with_error!(env, Error::NotAuthorized);
+        }
+
+        glyph_set_owner(&env, &token_id, Some(owner), Some(to.clone()));
+        glyph_history_add(&env, &token_id, Provenance::Transfer(from.clone(), to.clone()));
+
+        crate::events::glyph_transfer(
+            &env,
+            GlyphTransferEvent {
+                hash_type: HashType::Glyph(token_id.clone()),
+                spender: from.clone(),
+                from,
+                to,
+                received: HashType::Glyph(token_id),
+            },
+        );
+    }
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, token_id: BytesN<32>) {
+        spender.require_auth();
+
+        let owner = Self::owner_of(env.clone(), token_id.clone());
+
+        if owner != from || !glyph_is_approved(&env, &owner, &spender, &token_id) {
+            panic_with_error!(env, Error::NotAuthorized);
+        }
+
+        glyph_set_owner(&env, &token_id, Some(owner), Some(to.clone()));
+        glyph_history_add(&env, &token_id, Provenance::Transfer(from.clone(), to.clone()));
+
+        crate::events::glyph_transfer(
+            &env,
+            GlyphTransferEvent {
+                hash_type: HashType::Glyph(token_id.clone()),
+                spender,
+                from,
+                to,
+                received: HashType::Glyph(token_id),
+            },
+        );
+    }
+    fn approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        token_id: BytesN<32>,
+        live_until_ledger: u32,
+    ) {
+        approver.require_auth();
+
+        let owner = Self::owner_of(env.clone(), token_id.clone());
+
+        // Only the owner or one of their operators can hand out a glyph approval
+        if approver != owner && !Self::is_approved_for_all(env.clone(), owner.clone(), approver) {
+            panic_with_error!(env, Error::NotAuthorized);
+        }
+
+        // A zero ledger revokes the approval
+        if live_until_ledger == 0 {
+            remove_glyph_approval(&env, token_id.clone());
+        } else if live_until_ledger < env.ledger().sequence() {
+            panic_with_error!(env, Error::InvalidExpiration);
+        } else {
+            write_glyph_approval(
+                &env,
+                token_id.clone(),
+                &Approval {
+                    address: approved.clone(),
+                    live_until_ledger,
+                },
+            );
+        }
+
+        crate::events::glyph_approve(
+            &env,
+            GlyphApproveEvent {
+                owner,
+                approved,
+                hash: token_id,
+                live_until_ledger,
+            },
+        );
+    }
+    fn approve_for_all(env: Env, owner: Address, operator: Address, live_until_ledger: u32) {
+        owner.require_auth();
+
+        // A zero ledger revokes the operator
+        if live_until_ledger == 0 {
+            remove_glyph_operator(&env, owner.clone(), operator.clone());
+        } else if live_until_ledger < env.ledger().sequence() {
+            panic_with_error!(env, Error::InvalidExpiration);
+        } else {
+            write_glyph_operator(&env, owner.clone(), operator.clone(), live_until_ledger);
+        }
+
+        crate::events::glyph_approve_all(
+            &env,
+            GlyphApproveAllEvent {
+                owner,
+                operator,
+                live_until_ledger,
+            },
+        );
+    }
+    fn get_approved(env: Env, token_id: BytesN<32>) -> Option<Address> {
+        match read_glyph_approval(&env, token_id) {
+            Some(approval) if approval.live_until_ledger >= env.ledger().sequence() => {
+                Some(approval.address)
+            }
+            _ => None,
+        }
+    }
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        glyph_is_operator(&env, &owner, &operator)
+    }
+    fn name(env: Env) -> String {
+        String::from_str(&env, NAME)
+    }
+    fn symbol(env: Env) -> String {
+        String::from_str(&env, SYMBOL)
+    }
+    fn token_uri(env: Env, token_id: BytesN<32>) -> String {
+        Self::owner_of(env.clone(), token_id.clone());
+
+        // Without a base uri the glyph is served fully on chain
+        if read_base_uri(&env).len() == 0 {
+            let glyph = read_glyph(&env, token_id.clone()).unwrap_or_else(|e| panic_with_error!(&env, e));
+
+            return glyph_data_uri(&env, &glyph, read_glyph_transparent(&env, token_id));
+        }
+
+        glyph_uri(&env, &token_id)
+    }
 }
 
 #[contractimpl]
 impl Exchange for ColorGlyph {
-    fn offer_post(env: Env, sell: Offer, buy: Offer) -> Result<(), Error> {
-        offer_post(&env, sell, buy)
+    fn offer_post(env: Env, sell: Offer, buy: Offer, spender: Option<Address>) -> Result<(), Error> {
+        offer_post(&env, sell, buy, spender)
     }
-    fn offer_delete(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
-        offer_delete(&env, sell, buy)
+    fn offer_delete(env: Env, sell: Offer, buy: Option<Offer>, spender: Option<Address>) -> Result<(), Error> {
+        offer_delete(&env, sell, buy, spender)
     }
     fn offers_get(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
         offers_get(&env, sell, buy)
@@ -1,70 +1,288 @@
-use soroban_sdk::{symbol_short, Address, BytesN, Env, Map, Symbol, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, Env, IntoVal, Map, Symbol, Val, Vec};
 
-pub fn colors_mine(env: &Env, miner: &Address, to: &Address, colors: Map<u32, u32>) {
+use crate::types::{GlyphMetadata, HashType, MetadataPolicy, Offer, RoyaltyBreakdown};
+
+/* NOTE
+Every event is published with `(name, EVENT_VERSION, subject)` topics and one of the structs below as its data
+`subject` is whatever an indexer is most likely to filter on, the glyph hash for anything glyph or offer related, otherwise an address
+Bump `EVENT_VERSION` whenever a struct changes shape so old and new events can be decoded side by side
+Events carry both parties and every amount moved so an indexer can rebuild state from events alone
+    `glyph_mint` follows the `colors_out` events that built the glyph (pending under the minter until the mint)
+    `glyph_scrape` removes the owner, metadata and every sell offer, the `colors_in` event that follows says where the colors went
+        Whatever a scrape couldn't move stays pending in a new draft of the owner's until a `Colors` scrape picks it up
+        `glyph_scrape_complete` follows the call that moves the last of them
+Looped color moves publish one batched `colors_out` or `colors_in` per call rather than one per (miner, color) so big glyphs stay inside the event size limit
+    `offer_fill` swaps ownership (both ways for glyph swaps), clears every sell offer on the sold glyph and settles one asset offer
+        It also freezes `FrozenOnSale` metadata
+        Asset sales publish a `royalties_paid` just before it with how the price was split
+*/
+
+pub const EVENT_VERSION: u32 = 3; // 2 added draft ids, 3 replaced `royalty_payout` with `royalties_paid`
+
+fn publish<S, D>(env: &Env, name: &str, subject: S, data: D)
+where
+    S: IntoVal<Env, Val>,
+    D: IntoVal<Env, Val>,
+{
     env.events()
-        .publish((symbol_short!("mine"), miner, to), colors);
+        .publish((Symbol::new(env, name), EVENT_VERSION, subject), data);
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsMineEvent {
+    pub source: Address, // who paid
+    pub miner: Address,
+    pub to: Address,
+    pub colors: Map<u32, u32>,
+    pub price: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsTransferEvent {
+    pub spender: Address, // same as `from` unless moved through an allowance
+    pub from: Address,
+    pub to: Address,
+    pub colors: Vec<(Address, u32, u32)>, // miner, color, amount
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsBurnEvent {
+    pub owner: Address,
+    pub colors: Vec<(Address, u32, u32)>,
+    pub refund: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsApproveEvent {
+    pub owner: Address,
+    pub spender: Address,
+    pub miner: Address,
+    pub color: u32,
+    pub amount: u32,
+    pub expiration_ledger: u32,
+}
+
+// Everything one mint call moved out of an owner's balance into their pending glyph
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsOutEvent {
+    pub owner: Address,
+    pub colors: Map<Address, Map<u32, u32>>, // miner: color: amount
+}
+
+// Everything one scrape call moved back out of a scraped glyph
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorsInEvent {
+    pub owner: Address, // whose pending colors they came from
+    pub to: Address,
+    pub colors: Map<Address, Map<u32, u32>>, // miner: color: amount
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMintEvent {
+    pub minter: Address,
+    pub owner: Address,
+    pub hash: BytesN<32>,
+    pub width: u32,
+    pub transparent: bool,
+}
+
+// Colors were added to one of the minter's drafts without finishing it
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMintingEvent {
+    pub minter: Address,
+    pub draft: u32,
+}
+
+// Pixels taken out of a draft by `draft_erase` or painted over by `draft_paint`, their colors went back to the minter
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DraftEraseEvent {
+    pub minter: Address,
+    pub draft: u32,
+    pub colors: Map<Address, Map<u32, Vec<u32>>>, // miner: color: indexes
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphTransferEvent {
+    pub hash_type: HashType, // `Colors` moves a draft
+    pub spender: Address, // same as `from` unless moved by an approved address or operator
+    pub from: Address,
+    pub to: Address,
+    pub received: HashType, // what `to` now holds, the same glyph or a new draft of theirs
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphScrapeEvent {
+    pub hash_type: HashType, // `Colors` continues a partial scrape
+    pub owner: Address,
+    pub to: Address,
+    pub draft: u32, // the owner's draft holding whatever this scrape doesn't move
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphScrapeCompleteEvent {
+    pub owner: Address,
+    pub draft: u32,
+    pub to: Address, // where the last colors went
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphApproveEvent {
+    pub owner: Address,
+    pub approved: Address,
+    pub hash: BytesN<32>,
+    pub live_until_ledger: u32, // 0 revokes
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphApproveAllEvent {
+    pub owner: Address,
+    pub operator: Address,
+    pub live_until_ledger: u32, // 0 revokes
+}
+
+// Carries the full record so edits don't need a storage read
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMetadataEvent {
+    pub hash: BytesN<32>,
+    pub editor: Address,
+    pub metadata: GlyphMetadata,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMetadataPolicyEvent {
+    pub hash: BytesN<32>,
+    pub editor: Address,
+    pub policy: MetadataPolicy,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfferPostEvent {
+    pub owner: Address,
+    pub sell: Offer,
+    pub buy: Offer,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfferCancelEvent {
+    pub owner: Address,
+    pub sell: Offer, // an `AssetSell` cancel refunds its amount
+    pub buys: Vec<Offer>, // every buy offer that was cancelled
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OfferFillEvent {
+    pub hash: BytesN<32>,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: Offer, // the glyph swapped back to the seller or the `Asset` paid
+    pub proceeds: i128, // what the seller received after royalties
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoyaltiesPaidEvent {
+    pub hash: BytesN<32>,
+    pub asset: Address,
+    pub payer: Address, // the buyer, paid directly or out of their escrowed offer
+    pub seller: Address,
+    pub breakdown: RoyaltyBreakdown,
+}
+
+pub fn colors_mine(env: &Env, event: ColorsMineEvent) {
+    publish(env, "colors_mine", event.miner.clone(), event);
+}
+
+pub fn colors_transfer(env: &Env, event: ColorsTransferEvent) {
+    publish(env, "colors_transfer", event.from.clone(), event);
+}
+
+pub fn colors_burn(env: &Env, event: ColorsBurnEvent) {
+    publish(env, "colors_burn", event.owner.clone(), event);
+}
+
+pub fn colors_approve(env: &Env, event: ColorsApproveEvent) {
+    publish(env, "colors_approve", event.owner.clone(), event);
+}
+
+pub fn colors_out(env: &Env, event: ColorsOutEvent) {
+    publish(env, "colors_out", event.owner.clone(), event);
+}
+
+pub fn colors_in(env: &Env, event: ColorsInEvent) {
+    publish(env, "colors_in", event.to.clone(), event);
+}
+
+pub fn glyph_mint(env: &Env, event: GlyphMintEvent) {
+    publish(env, "glyph_mint", event.hash.clone(), event);
+}
+
+pub fn glyph_minting(env: &Env, event: GlyphMintingEvent) {
+    publish(env, "glyph_minting", event.minter.clone(), event);
+}
+
+pub fn draft_erase(env: &Env, event: DraftEraseEvent) {
+    publish(env, "draft_erase", event.minter.clone(), event);
+}
+
+pub fn glyph_transfer(env: &Env, event: GlyphTransferEvent) {
+    publish(env, "glyph_transfer", event.hash_type.clone(), event);
+}
+
+pub fn glyph_scrape(env: &Env, event: GlyphScrapeEvent) {
+    publish(env, "glyph_scrape", event.hash_type.clone(), event);
 }
 
-pub fn colors_transfer(env: &Env, from: &Address, to: &Address, colors:  Vec<(Address, u32, u32)>) {
-    env.events().publish(
-        (symbol_short!("transfer"), from, to),
-        colors,
-    );
+pub fn glyph_scrape_complete(env: &Env, event: GlyphScrapeCompleteEvent) {
+    publish(env, "glyph_scrape_complete", event.owner.clone(), event);
 }
 
-pub fn colors_out(env: &Env, miner: &Address, minter: &Address, color: u32, indexes_length: u32) {
-    env.events().publish(
-        (symbol_short!("color_out"), miner.clone(), minter.clone()),
-        (color, indexes_length),
-    );
+pub fn glyph_approve(env: &Env, event: GlyphApproveEvent) {
+    publish(env, "glyph_approve", event.hash.clone(), event);
 }
 
-pub fn minted_event(env: &Env, minter: &Address, to: Option<Address>, hash: &BytesN<32>) {
-    env.events().publish(
-        (symbol_short!("minted"), minter.clone(), to.clone()),
-        hash.clone(),
-    );
+pub fn glyph_approve_all(env: &Env, event: GlyphApproveAllEvent) {
+    publish(env, "glyph_approve_all", event.owner.clone(), event);
 }
 
-pub fn minting_event(env: &Env, minter: &Address) {
-    env.events().publish(
-        (symbol_short!("minting"), minter.clone()),
-        (),
-    );
+pub fn metadata_updated(env: &Env, event: GlyphMetadataEvent) {
+    publish(env, "metadata_updated", event.hash.clone(), event);
 }
 
-pub fn transfer_colors_event(env: &Env, from: &Address, to: &Address) {
-    env.events().publish(
-        (Symbol::new(&env, "transfer_colors"), from.clone(), to.clone()),
-        (),
-    );
+pub fn glyph_metadata_policy(env: &Env, event: GlyphMetadataPolicyEvent) {
+    publish(env, "glyph_metadata_policy", event.hash.clone(), event);
 }
 
-// Note this event has changed to avoid unnecessary enlargement of soroban meta.
-pub fn transfer_glyph_event(env: &Env, to: &Address, glyph_hash: &BytesN<32>) {
-    env.events().publish(
-        (Symbol::new(&env, "transfer_glyph"), glyph_hash.clone(), to.clone()),
-        ()
-    );
+pub fn offer_post(env: &Env, hash: &BytesN<32>, event: OfferPostEvent) {
+    publish(env, "offer_post", hash.clone(), event);
 }
 
-pub fn scrape_colors_event(env: &Env, colors_owner: &Address, to: Option<Address>) {
-    env.events().publish(
-        (Symbol::new(&env, "scrape_colors"), colors_owner.clone(), to.clone()),
-        (),
-    );
+pub fn offer_cancel(env: &Env, hash: &BytesN<32>, event: OfferCancelEvent) {
+    publish(env, "offer_cancel", hash.clone(), event);
 }
 
-pub fn scrape_glyph_event(env: &Env, owner: &Address, to: Option<Address>, glyph_hash: &BytesN<32>) {
-    env.events().publish(
-        (Symbol::new(&env, "scrape_glyph"), owner.clone(), to.clone()),
-        glyph_hash.clone(),
-    );
+pub fn offer_fill(env: &Env, event: OfferFillEvent) {
+    publish(env, "offer_fill", event.hash.clone(), event);
 }
 
-pub fn color_in_event(env: &Env, miner: &Address, to_address: &Address, color: u32, indexes_length: u32) {
-    env.events().publish(
-        (symbol_short!("color_in"), miner.clone(), to_address.clone()),
-        (color, indexes_length),
-    );
+pub fn royalties_paid(env: &Env, event: RoyaltiesPaidEvent) {
+    publish(env, "royalties_paid", event.hash.clone(), event);
 }
@@ -2,26 +2,335 @@
 // extern crate std;
 
 use crate::{
-    contract::MAX_BIT24_SIZE,
-    types::{Error, Glyph, StorageKey},
+    colors::{color_credit, color_debit},
+    contract::{MAX_BIT24_SIZE, MAX_PIXELS},
+    events::{ColorsOutEvent, DraftEraseEvent, GlyphMetadataEvent, GlyphMintEvent, GlyphMintingEvent},
+    history::glyph_history_add,
+    metadata::glyph_metadata_verify,
+    royalties::MAX_SALE_PAYMENTS,
+    storage::{
+        instance::{read_base_uri, read_canvas, read_remint_policy},
+        persistent::{
+            read_draft_or_error, read_draft_or_map, read_drafts, read_glyph_approval, read_glyph_minter,
+            read_glyph_operator, read_glyph_owner, read_legacy_colors, read_owner_glyph, read_owner_glyph_count,
+            read_owner_glyph_position, remove_draft, remove_glyph_approval, remove_glyph_offer, remove_glyph_owner,
+            remove_legacy_colors, remove_owner_glyph, remove_owner_glyph_position, write_draft, write_glyph_metadata,
+            write_glyph_minter, write_glyph_owner, write_glyph_transparent, write_owner_glyph, write_owner_glyph_count,
+        },
+    },
+    types::{
+        Error, Glyph, GlyphPreview, GlyphStatus, MintMetadata, MintOptions, PixelSpan, Provenance, RemintPolicy,
+        ScrapeProgress, StorageKey,
+    },
 };
-use soroban_sdk::{panic_with_error, Address, Bytes, BytesN, Env, Map, Vec};
+use core::cmp::Ordering;
+
+use soroban_sdk::{panic_with_error, Address, Bytes, BytesN, Env, Map, String, Vec};
+
+pub const MAX_BASE_URI_SIZE: usize = 192;
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+pub fn glyph_mint(
+    env: &Env,
+    minter: Address,
+    to: Option<Address>,
+    colors: Map<Address, Map<u32, Vec<u32>>>,
+    width: Option<u32>,
+    options: MintOptions,
+) -> Option<BytesN<32>> {
+    let MintOptions {
+        metadata,
+        transparent,
+        draft,
+    } = options;
+
+    let metadata = match metadata {
+        MintMetadata::None => None,
+        MintMetadata::Some(metadata) => Some(metadata),
+    };
+
+    // Plain mints keep building draft 0 like before drafts existed
+    let draft = draft.unwrap_or(0);
+
+    // Metadata and transparency only apply to the finished glyph
+    if width.is_none() && (metadata.is_some() || transparent) {
+        panic_with_error!(env, Error::MissingWidth);
+    }
+
+    if let Some(metadata) = &metadata {
+        glyph_metadata_verify(env, metadata);
+    }
+
+    let mut glyph_colors = read_draft_or_map(env, minter.clone(), draft);
+
+    glyph_verify_indexes(env, &glyph_colors, &colors);
+
+    let mut spent: Map<Address, Map<u32, u32>> = Map::new(env);
+
+    // spend colors
+    for (miner, color_indexes) in colors.iter() {
+        let mut skip = false;
+        let mut miner_spent: Map<u32, u32> = Map::new(env);
+
+        for (color, indexes) in color_indexes.iter() {
+            color_debit(env, &miner, &minter, color, indexes.len());
+
+            miner_spent.set(color, indexes.len());
+
+            if !skip {
+                match glyph_colors.get(miner.clone()) {
+                    Some(result) => match result {
+                        mut color_indexes_ => match color_indexes_.get(color) {
+                            // Existing miner and color
+                            Some(result) => match result {
+                                mut indexes_ => {
+                                    indexes_.append(&indexes);
+                                    color_indexes_.set(color, indexes_);
+                                    glyph_colors.set(miner.clone(), color_indexes_);
+                                }
+                            },
+                            // Existing miner no color
+                            None => {
+                                color_indexes_.set(color, indexes);
+                                glyph_colors.set(miner.clone(), color_indexes_);
+                            }
+                        },
+                    },
+                    // No miner (or no exisiting Colors)
+                    None => {
+                        glyph_colors.set(miner.clone(), color_indexes.clone());
+                        // We set a skip vs using break to ensure we continue to bill for the spent colors
+                        skip = true; // we need to break here otherwise we continue looping inside this nested color loop which we've already fully added
+                    }
+                }
+            }
+        }
+
+        spent.set(miner, miner_spent);
+    }
+
+    crate::events::colors_out(
+        env,
+        ColorsOutEvent {
+            owner: minter.clone(),
+            colors: spent,
+        },
+    );
+
+    match width {
+        // We are storing the glyph
+        Some(width) => {
+            let hash = glyph_store(env, minter.clone(), to.clone(), glyph_colors, width, transparent);
+
+            remove_draft(env, minter.clone(), draft);
+
+            crate::events::glyph_mint(
+                env,
+                GlyphMintEvent {
+                    minter: minter.clone(),
+                    owner: to.unwrap_or(minter.clone()),
+                    hash: hash.clone(),
+                    width,
+                    transparent,
+                },
+            );
+
+            if let Some(metadata) = metadata {
+                write_glyph_metadata(env, hash.clone(), &metadata);
+
+                crate::events::metadata_updated(
+                    env,
+                    GlyphMetadataEvent {
+                        hash: hash.clone(),
+                        editor: minter,
+                        metadata,
+                    },
+                );
+            }
+
+            Some(hash)
+        }
+        // We are building the glyph
+        None => {
+            write_draft(env, minter.clone(), draft, &glyph_colors);
+            crate::events::glyph_minting(env, GlyphMintingEvent { minter, draft });
+
+            None
+        }
+    }
+}
+
+// Expands ranges, runs and row spans into the plain indexes `glyph_mint` takes so they hash exactly the same
+pub fn glyph_spans_expand(
+    env: &Env,
+    colors: &Map<Address, Map<u32, Vec<PixelSpan>>>,
+    width: Option<u32>,
+) -> Map<Address, Map<u32, Vec<u32>>> {
+    let mut expanded = Map::new(env);
+    let mut count: usize = 0;
+
+    for (miner, color_spans) in colors.iter() {
+        let mut color_indexes = Map::new(env);
+
+        for (color, spans) in color_spans.iter() {
+            let mut indexes = Vec::new(env);
+
+            for span in spans.iter() {
+                let (start, end) = match span {
+                    PixelSpan::Index(index) => (index, index),
+                    PixelSpan::Range(start, end) => (start, end),
+                    PixelSpan::Run(start, length) => {
+                        if length == 0 {
+                            panic_with_error!(env, Error::IndexOutOfRange);
+                        }
+
+                        (start, start.saturating_add(length - 1))
+                    }
+                    PixelSpan::Row(row, start, end) => {
+                        let width = width.unwrap_or_else(|| panic_with_error!(env, Error::MissingWidth));
+
+                        if end >= width {
+                            panic_with_error!(env, Error::IndexOutOfRange);
+                        }
+
+                        (row.saturating_mul(width).saturating_add(start), row.saturating_mul(width).saturating_add(end))
+                    }
+                };
+
+                if start > end || end as usize >= MAX_PIXELS {
+                    panic_with_error!(env, Error::IndexOutOfRange);
+                }
+
+                // Anything past a full canvas has to be overlapping
+                count += (end - start + 1) as usize;
+
+                if count > MAX_PIXELS {
+                    panic_with_error!(env, Error::DuplicateIndex);
+                }
+
+                for index in start..=end {
+                    indexes.push_back(index);
+                }
+            }
+
+            color_indexes.set(color, indexes);
+        }
+
+        expanded.set(miner, color_indexes);
+    }
+
+    expanded
+}
 
 pub fn glyph_store(
     env: &Env,
     minter: Address,
     to: Option<Address>,
     colors: Map<Address, Map<u32, Vec<u32>>>,
-    width: u8,
+    width: u32,
+    transparent: bool,
 ) -> BytesN<32> {
+    // The minter, every miner and the seller could each need paying on a sale, don't mint what couldn't be sold
+    if colors.len() + 2 > MAX_SALE_PAYMENTS {
+        panic_with_error!(env, Error::PaymentCapReached);
+    }
+
+    let (hash, length) = glyph_hash(env, &colors, width, transparent);
+    let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
+
+    // Glyph has already been minted and is currently owned (not scraped)
+    if env.storage().persistent().has(&glyph_owner_key) {
+        panic_with_error!(env, Error::GlyphOwned);
+    }
+
+    let owner = to.unwrap_or(minter.clone());
+
+    // Save the glyph owner to storage
+    glyph_set_owner(env, &hash, None, Some(owner.clone()));
+
+    // Save the glyph minter to storage, re-mints of scraped glyphs leave it to the `RemintPolicy`
+    let record = match read_glyph_minter(env, hash.clone()) {
+        None => {
+            write_glyph_minter(env, hash.clone(), &minter);
+
+            Provenance::Mint(minter, owner)
+        }
+        Some(glyph_minter) => {
+            let glyph_minter = match read_remint_policy(env) {
+                RemintPolicy::OriginalMinter => glyph_minter,
+                RemintPolicy::LatestMinter => {
+                    write_glyph_minter(env, hash.clone(), &minter);
+
+                    minter.clone()
+                }
+            };
+
+            Provenance::Remint(minter, owner, glyph_minter)
+        }
+    };
+
+    glyph_history_add(env, &hash, record);
+
+    // Part of the hash so it sticks around with the glyph through scrapes
+    if transparent {
+        write_glyph_transparent(env, hash.clone());
+    }
+
+    // Save the glyph to storage
+    // Re-mints overwrite it as the pixels hash the same but whoever mined them may not, scrapes and royalties go to the miners in it now
+    env.storage().persistent().set(
+        &StorageKey::Glyph(hash.clone()),
+        &Glyph {
+            width,
+            length,
+            colors,
+        },
+    );
+
+    hash
+}
+
+// What `glyph_store` would do with these colors without writing anything, indexes are checked as if minting from scratch
+pub fn glyph_preview(
+    env: &Env,
+    colors: &Map<Address, Map<u32, Vec<u32>>>,
+    width: u32,
+    transparent: bool,
+) -> GlyphPreview {
+    glyph_verify_indexes(env, &Map::new(env), colors);
+
+    let (hash, length) = glyph_hash(env, colors, width, transparent);
+
+    let mut pixels = 0;
+
+    for (_, color_indexes) in colors.iter() {
+        for (_, indexes) in color_indexes.iter() {
+            pixels += indexes.len();
+        }
+    }
+
+    let status = match read_glyph_owner(env, hash.clone()) {
+        Some(owner) => GlyphStatus::Owned(owner),
+        None => match read_glyph_minter(env, hash.clone()) {
+            Some(minter) => GlyphStatus::Minted(minter),
+            None => GlyphStatus::Free,
+        },
+    };
+
+    GlyphPreview {
+        hash,
+        length,
+        pixels,
+        status,
+    }
+}
+
+// The glyph's hash and length in pixels (up to its last colored one)
+fn glyph_hash(env: &Env, colors: &Map<Address, Map<u32, Vec<u32>>>, width: u32, transparent: bool) -> (BytesN<32>, u32) {
     let mut max_i = 0;
     let mut bit24_data = [u8::MAX; MAX_BIT24_SIZE];
+    let mut mask = [0u8; MAX_PIXELS / 8 + 1];
 
-    /* TODO
-    Better error for not enough colors
-    Should we error if there's a dupe index?
-    Should we enable some concept of ranging between 2 indexs vs listing out all the indexes? 0..=5 vs 0,1,2,3,4,5
-    */
     for (_, color_indexes) in colors.iter() {
         for (color, indexes) in color_indexes.iter() {
             for index in indexes.iter() {
@@ -33,6 +342,8 @@ pub fn glyph_store(
                 bit24_data[i + 1] = g;
                 bit24_data[i + 2] = b;
 
+                mask[index as usize / 8] |= 1 << (index % 8);
+
                 if i + 2 > max_i {
                     max_i = i + 2;
                 }
@@ -40,65 +351,109 @@ pub fn glyph_store(
         }
     }
 
-    bit24_data[max_i + 1] = width;
+    let canvas = read_canvas(env);
 
-    let bytes = Bytes::from_slice(&env, &bit24_data[..=(max_i + 1)]);
+    // The glyph can't run off the bottom of the canvas
+    if width == 0 || width > canvas.max_width || (max_i / 3) as u32 / width >= canvas.max_height {
+        panic_with_error!(env, Error::InvalidWidth);
+    }
 
-    let hash = env.crypto().sha256(&bytes);
-    let glyph_owner_key = StorageKey::GlyphOwner(hash.clone());
+    let length = glyph_preimage_trailer(&mut bit24_data, max_i + 1, width, if transparent { Some(&mask) } else { None });
 
-    // Glyph has already been minted and is currently owned (not scraped)
-    if env.storage().persistent().has(&glyph_owner_key) {
-        panic_with_error!(env, Error::NotEmpty);
-    }
+    let bytes = Bytes::from_slice(env, &bit24_data[..length]);
 
-    // Save the glyph owner to storage
-    env.storage().persistent().set(
-        &glyph_owner_key,
-        &match to {
-            Some(address) => address,
-            None => minter.clone(),
-        },
-    );
+    (env.crypto().sha256(&bytes), (max_i as u32 + 1) / 3)
+}
 
-    // Save the glyph minter to storage (if glyph hasn't already been minted)
-    let glyph_minter_key = StorageKey::GlyphMinter(hash.clone());
+/* NOTE
+Glyphs up to 255 wide keep the original preimage, rgb followed by a single width byte (length % 3 == 1), so their hashes never change
+Anything wider is rgb followed by the full u32 width and a u16 version of 1 (length % 3 == 0) so the two can't collide
+Transparent glyphs are version 2, rgb then a bitmask of the colored pixels, zero padding, the u32 width and the version (length % 3 == 2)
+Uncolored pixels are still 0xffffff in the rgb, the mask is what tells them apart from white
+*/
+fn glyph_preimage_trailer(bit24_data: &mut [u8], rgb_length: usize, width: u32, mask: Option<&[u8]>) -> usize {
+    let mut length = rgb_length;
 
-    if !env.storage().persistent().has(&glyph_minter_key) {
-        env.storage().persistent().set(&glyph_minter_key, &minter);
-    }
+    let version: u16 = match mask {
+        Some(mask) => {
+            let mask_length = (rgb_length / 3).div_ceil(8);
 
-    // Save the glyph to storage
-    let glyph_key = StorageKey::Glyph(hash.clone());
+            bit24_data[length..length + mask_length].copy_from_slice(&mask[..mask_length]);
+            length += mask_length;
+
+            // Pad so the whole preimage lands on length % 3 == 2
+            while (length + 6) % 3 != 2 {
+                bit24_data[length] = 0;
+                length += 1;
+            }
+
+            2
+        }
+        None if width <= u8::MAX as u32 => {
+            bit24_data[length] = width as u8;
+
+            return length + 1;
+        }
+        None => 1,
+    };
 
-    // Only save the glyph if it hasn't already been minted
-    if !env.storage().persistent().has(&glyph_key) {
-        env.storage().persistent().set(
-            &glyph_key,
-            &Glyph {
-                width: width as u32,
-                length: (bytes.len() - 1) / 3, // remove one byte for the length and divide by 3 for the RGB
-                colors,
-            },
-        );
+    bit24_data[length..length + 4].copy_from_slice(&width.to_be_bytes());
+    bit24_data[length + 4..length + 6].copy_from_slice(&version.to_be_bytes());
+
+    length + 6
+}
+
+// Every index has to land on the canvas and can only be colored once per glyph, including across progressive mints
+pub fn glyph_verify_indexes(
+    env: &Env,
+    glyph_colors: &Map<Address, Map<u32, Vec<u32>>>,
+    colors: &Map<Address, Map<u32, Vec<u32>>>,
+) {
+    let canvas = read_canvas(env);
+    let max_index = canvas.max_width * canvas.max_height;
+
+    let mut used = [0u8; MAX_PIXELS / 8 + 1];
+
+    for (_, color_indexes) in glyph_colors.iter() {
+        for (_, indexes) in color_indexes.iter() {
+            for index in indexes.iter() {
+                used[index as usize / 8] |= 1 << (index % 8);
+            }
+        }
     }
 
-    // Remove any temp Colors
-    env.storage()
-        .persistent()
-        .remove(&StorageKey::Colors(minter));
+    for (_, color_indexes) in colors.iter() {
+        for (_, indexes) in color_indexes.iter() {
+            for index in indexes.iter() {
+                if index >= max_index {
+                    panic_with_error!(env, Error::IndexOutOfRange);
+                }
+
+                if used[index as usize / 8] & 1 << (index % 8) != 0 {
+                    panic_with_error!(env, Error::DuplicateIndex);
+                }
 
-    hash
+                used[index as usize / 8] |= 1 << (index % 8);
+            }
+        }
+    }
 }
 
-pub fn glyph_verify_ownership(env: &Env, glyph_owner_key: &StorageKey) -> Address {
-    let glyph_owner = env
-        .storage()
-        .persistent()
-        .get::<StorageKey, Address>(glyph_owner_key)
+// Returns the glyph owner once either they or an approved `spender` have authorized the call
+pub fn glyph_verify_ownership(env: &Env, hash: &BytesN<32>, spender: &Option<Address>) -> Address {
+    let glyph_owner = read_glyph_owner(env, hash.clone())
         .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
 
-    glyph_owner.require_auth();
+    match spender {
+        Some(spender) => {
+            spender.require_auth();
+
+            if !glyph_is_approved(env, &glyph_owner, spender, hash) {
+                panic_with_error!(env, Error::NotAuthorized);
+            }
+        }
+        None => glyph_owner.require_auth(),
+    }
 
     // env.storage()
     //     .persistent()
@@ -106,3 +461,315 @@ pub fn glyph_verify_ownership(env: &Env, glyph_owner_key: &StorageKey) -> Addres
 
     glyph_owner
 }
+
+// Every change of glyph ownership should run through here so the owner index, approvals and sell offers stay in sync
+pub fn glyph_set_owner(env: &Env, hash: &BytesN<32>, from: Option<Address>, to: Option<Address>) {
+    if let Some(from) = from {
+        owner_glyphs_remove(env, &from, hash);
+
+        // Glyph approvals and the old owner's sell offers never survive a change of owner
+        remove_glyph_approval(env, hash.clone());
+        remove_glyph_offer(env, hash.clone());
+    }
+
+    match to {
+        Some(to) => {
+            owner_glyphs_add(env, &to, hash);
+            write_glyph_owner(env, hash.clone(), &to);
+        }
+        None => remove_glyph_owner(env, hash.clone()),
+    }
+}
+
+/* NOTE
+An owner's glyphs are listed one entry per glyph under `OwnerGlyph(owner, position)` so no single entry grows with how many glyphs someone is sent
+Each glyph remembers its position so a removal swaps the last glyph into the gap instead of scanning, which means `glyphs_of` order isn't mint order
+Glyphs owned before the index existed have no position until someone calls `glyph_index` for them
+*/
+
+fn owner_glyphs_add(env: &Env, owner: &Address, hash: &BytesN<32>) {
+    let count = read_owner_glyph_count(env, owner.clone());
+
+    write_owner_glyph(env, owner.clone(), count, hash);
+    write_owner_glyph_count(env, owner.clone(), count + 1);
+}
+
+fn owner_glyphs_remove(env: &Env, owner: &Address, hash: &BytesN<32>) {
+    // Only an unindexed (pre index) glyph has no position and then there's nothing to remove
+    let Some(position) = read_owner_glyph_position(env, hash.clone()) else {
+        return;
+    };
+
+    let last = read_owner_glyph_count(env, owner.clone()) - 1;
+
+    if position != last {
+        let last_hash = read_owner_glyph(env, owner.clone(), last).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+        write_owner_glyph(env, owner.clone(), position, &last_hash);
+    }
+
+    remove_owner_glyph(env, owner.clone(), last);
+    remove_owner_glyph_position(env, hash.clone());
+    write_owner_glyph_count(env, owner.clone(), last);
+}
+
+// Backfills the owner index for a glyph owned from before it existed, does nothing for glyphs already listed
+pub fn glyph_index(env: &Env, hash: &BytesN<32>) {
+    let owner = read_glyph_owner(env, hash.clone()).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+    if read_owner_glyph_position(env, hash.clone()).is_none() {
+        owner_glyphs_add(env, &owner, hash);
+    }
+}
+
+pub fn glyphs_of(env: &Env, owner: &Address, cursor: u32, limit: u32) -> Vec<BytesN<32>> {
+    let end = cursor
+        .saturating_add(limit.min(MAX_PAGE_SIZE))
+        .min(read_owner_glyph_count(env, owner.clone()));
+    let mut glyphs = Vec::new(env);
+
+    for position in cursor..end {
+        if let Some(hash) = read_owner_glyph(env, owner.clone(), position) {
+            glyphs.push_back(hash);
+        }
+    }
+
+    glyphs
+}
+
+// In progress Colors can only be handled by their owner or one of the owner's operators
+pub fn colors_verify_ownership(env: &Env, owner: &Address, spender: &Option<Address>) {
+    match spender {
+        Some(spender) => {
+            spender.require_auth();
+
+            if !glyph_is_operator(env, owner, spender) {
+                panic_with_error!(env, Error::NotAuthorized);
+            }
+        }
+        None => owner.require_auth(),
+    }
+}
+
+// Takes exactly the given pixels out of a draft, each has to be there under the same miner and color
+pub fn draft_erase(env: &Env, minter: &Address, draft: u32, colors: &Map<Address, Map<u32, Vec<u32>>>) {
+    let mut draft_colors = read_draft_or_error(env, minter.clone(), draft);
+
+    for (miner, color_indexes) in colors.iter() {
+        let mut draft_color_indexes = draft_colors.get(miner.clone()).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+        for (color, indexes) in color_indexes.iter() {
+            let mut draft_indexes = draft_color_indexes.get(color).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+            for index in indexes.iter() {
+                match draft_indexes.first_index_of(index) {
+                    Some(i) => draft_indexes.remove(i),
+                    None => panic_with_error!(env, Error::NotFound),
+                };
+            }
+
+            if draft_indexes.is_empty() {
+                draft_color_indexes.remove(color);
+            } else {
+                draft_color_indexes.set(color, draft_indexes);
+            }
+        }
+
+        if draft_color_indexes.is_empty() {
+            draft_colors.remove(miner);
+        } else {
+            draft_colors.set(miner, draft_color_indexes);
+        }
+    }
+
+    draft_refund(env, minter, draft, &draft_colors, colors.clone());
+}
+
+// Overwrite mode, takes whatever is already painted at any of the indexes `colors` is about to paint
+pub fn draft_clear(env: &Env, minter: &Address, draft: u32, colors: &Map<Address, Map<u32, Vec<u32>>>) {
+    let canvas = read_canvas(env);
+    let max_index = canvas.max_width * canvas.max_height;
+
+    let mut painting = [0u8; MAX_PIXELS / 8 + 1];
+
+    for (_, color_indexes) in colors.iter() {
+        for (_, indexes) in color_indexes.iter() {
+            for index in indexes.iter() {
+                if index >= max_index {
+                    panic_with_error!(env, Error::IndexOutOfRange);
+                }
+
+                painting[index as usize / 8] |= 1 << (index % 8);
+            }
+        }
+    }
+
+    let mut draft_colors = read_draft_or_map(env, minter.clone(), draft);
+    let mut taken: Map<Address, Map<u32, Vec<u32>>> = Map::new(env);
+
+    for (miner, mut draft_color_indexes) in draft_colors.iter() {
+        let mut miner_taken: Map<u32, Vec<u32>> = Map::new(env);
+
+        for (color, draft_indexes) in draft_color_indexes.iter() {
+            let mut kept: Vec<u32> = Vec::new(env);
+            let mut color_taken: Vec<u32> = Vec::new(env);
+
+            for index in draft_indexes.iter() {
+                if painting[index as usize / 8] & 1 << (index % 8) != 0 {
+                    color_taken.push_back(index);
+                } else {
+                    kept.push_back(index);
+                }
+            }
+
+            if color_taken.is_empty() {
+                continue;
+            }
+
+            miner_taken.set(color, color_taken);
+
+            if kept.is_empty() {
+                draft_color_indexes.remove(color);
+            } else {
+                draft_color_indexes.set(color, kept);
+            }
+        }
+
+        if miner_taken.is_empty() {
+            continue;
+        }
+
+        taken.set(miner.clone(), miner_taken);
+
+        if draft_color_indexes.is_empty() {
+            draft_colors.remove(miner);
+        } else {
+            draft_colors.set(miner, draft_color_indexes);
+        }
+    }
+
+    if !taken.is_empty() {
+        draft_refund(env, minter, draft, &draft_colors, taken);
+    }
+}
+
+// Saves what's left of the draft and credits everything `taken` back to the minter
+fn draft_refund(
+    env: &Env,
+    minter: &Address,
+    draft: u32,
+    draft_colors: &Map<Address, Map<u32, Vec<u32>>>,
+    taken: Map<Address, Map<u32, Vec<u32>>>,
+) {
+    for (miner, color_indexes) in taken.iter() {
+        for (color, indexes) in color_indexes.iter() {
+            color_credit(env, &miner, minter, color, indexes.len());
+        }
+    }
+
+    if draft_colors.is_empty() {
+        remove_draft(env, minter.clone(), draft);
+    } else {
+        write_draft(env, minter.clone(), draft, draft_colors);
+    }
+
+    crate::events::draft_erase(
+        env,
+        DraftEraseEvent {
+            minter: minter.clone(),
+            draft,
+            colors: taken,
+        },
+    );
+}
+
+// The lowest unused draft id above 0, draft 0 is left for plain `glyph_mint` builds
+pub fn draft_next(env: &Env, owner: &Address) -> u32 {
+    let mut next = 1;
+
+    for draft in read_drafts(env, owner.clone()).iter() {
+        match draft.cmp(&next) {
+            Ordering::Less => {} // draft 0
+            Ordering::Equal => next += 1,
+            Ordering::Greater => break,
+        }
+    }
+
+    next
+}
+
+// Moves a build left under the pre-draft `Colors(owner)` key into draft 0, or the next free draft if 0 is in use
+pub fn draft_recover_legacy(env: &Env, owner: &Address) -> u32 {
+    let colors = read_legacy_colors(env, owner.clone()).unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+    let draft = match read_drafts(env, owner.clone()).first() {
+        Some(0) => draft_next(env, owner),
+        _ => 0,
+    };
+
+    remove_legacy_colors(env, owner.clone());
+    write_draft(env, owner.clone(), draft, &colors);
+
+    draft
+}
+
+// Counts what a `Colors` scrape still has to move out of `colors`
+pub fn glyph_scrape_progress(draft: u32, colors: &Map<Address, Map<u32, Vec<u32>>>) -> ScrapeProgress {
+    let mut progress = ScrapeProgress {
+        draft: if colors.is_empty() { None } else { Some(draft) },
+        miners: colors.len(),
+        colors: 0,
+        pixels: 0,
+    };
+
+    for (_, colors_indexes) in colors.iter() {
+        progress.colors += colors_indexes.len();
+
+        for (_, indexes) in colors_indexes.iter() {
+            progress.pixels += indexes.len();
+        }
+    }
+
+    progress
+}
+
+pub fn glyph_is_operator(env: &Env, owner: &Address, operator: &Address) -> bool {
+    match read_glyph_operator(env, owner.clone(), operator.clone()) {
+        Some(live_until_ledger) => live_until_ledger >= env.ledger().sequence(),
+        None => false,
+    }
+}
+
+pub fn glyph_is_approved(env: &Env, owner: &Address, spender: &Address, hash: &BytesN<32>) -> bool {
+    if spender == owner {
+        return true;
+    }
+
+    let sequence = env.ledger().sequence();
+
+    if let Some(approval) = read_glyph_approval(env, hash.clone()) {
+        if approval.address == *spender && approval.live_until_ledger >= sequence {
+            return true;
+        }
+    }
+
+    glyph_is_operator(env, owner, spender)
+}
+
+// `base_uri` followed by the lowercase hex glyph hash
+pub fn glyph_uri(env: &Env, hash: &BytesN<32>) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let base_uri = read_base_uri(env);
+    let base_uri_len = base_uri.len() as usize;
+    let mut uri = [0u8; MAX_BASE_URI_SIZE + 64];
+
+    base_uri.copy_into_slice(&mut uri[..base_uri_len]);
+
+    for (i, byte) in hash.to_array().iter().enumerate() {
+        uri[base_uri_len + i * 2] = HEX[(byte >> 4) as usize];
+        uri[base_uri_len + i * 2 + 1] = HEX[(byte & 0xf) as usize];
+    }
+
+    String::from_bytes(env, &uri[..base_uri_len + 64])
+}
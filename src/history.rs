@@ -0,0 +1,40 @@
+use soroban_sdk::{BytesN, Env, Vec};
+
+use crate::{
+    glyphs::MAX_PAGE_SIZE,
+    storage::persistent::{read_glyph_history_entry, read_glyph_history_length, write_glyph_history_entry},
+    types::{Provenance, ProvenanceEntry},
+};
+
+/* NOTE
+Every glyph keeps an append only provenance log under its hash, mints and re-mints, transfers, sales and scrapes, each with its ledger
+It outlives scrapes so a re-minted glyph still shows everything that happened to the art before
+Records sit next to the matching events, the log is for reading a glyph's story back on chain without an indexer
+*/
+
+pub fn glyph_history_add(env: &Env, hash: &BytesN<32>, record: Provenance) {
+    write_glyph_history_entry(
+        env,
+        hash.clone(),
+        &ProvenanceEntry {
+            ledger: env.ledger().sequence(),
+            record,
+        },
+    );
+}
+
+// Oldest first, a page at a time
+pub fn glyph_history(env: &Env, hash: &BytesN<32>, cursor: u32) -> Vec<ProvenanceEntry> {
+    let length = read_glyph_history_length(env, hash.clone());
+    let end = cursor.saturating_add(MAX_PAGE_SIZE).min(length);
+
+    let mut entries = Vec::new(env);
+
+    for index in cursor..end {
+        if let Some(entry) = read_glyph_history_entry(env, hash.clone(), index) {
+            entries.push_back(entry);
+        }
+    }
+
+    entries
+}
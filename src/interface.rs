@@ -1,6 +1,6 @@
-use soroban_sdk::{Address, BytesN, Env, Map, Vec};
+use soroban_sdk::{Address, Bytes, BytesN, Env, Map, String, Vec};
 
-use crate::types::{Error, GlyphType, HashType, Offer};
+use crate::types::{Canvas, Error, GlyphMetadata, GlyphPreview, GlyphType, HashType, MetadataPolicy, MintOptions, Offer, PixelSpan, PriceCurve, ProvenanceEntry, RemintPolicy, RenderFormat, ScrapeProgress};
 
 pub trait ColorGlyphTrait {
     fn initialize(env: Env, owner_address: Address, token_address: Address, fee_address: Address);
@@ -14,6 +14,21 @@ pub trait ColorGlyphTrait {
         minter_royalty_rate: Option<i128>,
         miner_royalty_rate: Option<i128>,
     );
+    fn update_base_uri(env: Env, base_uri: String);
+    fn update_mine_price(
+        env: Env,
+        base_price: Option<i128>,
+        color_multipliers: Option<Map<u32, u32>>,
+        volume_discounts: Option<Vec<(u32, u32)>>,
+    );
+    fn update_price_curve(env: Env, price_curve: PriceCurve);
+    fn update_canvas(env: Env, max_width: u32, max_height: u32);
+    fn update_burn_refund_rate(env: Env, refund_rate: i128);
+    fn update_remint_policy(env: Env, policy: RemintPolicy);
+    fn remint_policy(env: Env) -> RemintPolicy;
+    fn treasury_deposit(env: Env, source: Address, amount: i128);
+    fn treasury_withdraw(env: Env, to: Address, amount: i128);
+    fn treasury_balance(env: Env) -> i128;
     fn upgrade(env: Env, hash: BytesN<32>);
 }
 
@@ -24,9 +39,34 @@ pub trait ColorsInterface {
         colors: Map<u32, u32>,
         miner: Option<Address>,
         to: Option<Address>,
+        max_total_price: Option<i128>,
     );
+    fn colors_quote(env: Env, colors: Map<u32, u32>) -> i128;
+    fn color_supply(env: Env, color: u32) -> u64;
     fn colors_transfer(env: Env, from: Address, to: Address, colors: Vec<(Address, u32, u32)>);
+    fn colors_burn(env: Env, owner: Address, colors: Vec<(Address, u32, u32)>) -> i128;
+    fn color_burned(env: Env, color: u32) -> u64;
+    fn colors_approve(
+        env: Env,
+        owner: Address,
+        spender: Address,
+        miner: Address,
+        color: u32,
+        amount: u32,
+        expiration_ledger: u32,
+    );
+    fn colors_allowance(env: Env, owner: Address, spender: Address, miner: Address, color: u32) -> u32;
+    fn colors_transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        colors: Vec<(Address, u32, u32)>,
+    );
     fn color_balance(env: Env, owner: Address, color: u32, miner: Option<Address>) -> u32;
+    fn colors_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<(Address, u32, u32)>;
+    fn color_total(env: Env, owner: Address, color: u32) -> u32;
+    fn color_index(env: Env, owner: Address, miner: Address, color: u32);
 }
 
 pub trait GlyphInterface {
@@ -36,14 +76,77 @@ pub trait GlyphInterface {
         to: Option<Address>,
         colors: Map<Address, Map<u32, Vec<u32>>>,
         width: Option<u32>,
+        options: Option<MintOptions>,
+    ) -> Option<BytesN<32>>;
+    fn glyph_mint_spans(
+        env: Env,
+        minter: Address,
+        to: Option<Address>,
+        colors: Map<Address, Map<u32, Vec<PixelSpan>>>,
+        width: Option<u32>,
+        options: Option<MintOptions>,
     ) -> Option<BytesN<32>>;
-    fn glyph_transfer(env: Env, to: Address, hash_type: HashType);
-    fn glyph_scrape(env: Env, to: Option<Address>, hash_type: HashType);
+    fn draft_paint(env: Env, minter: Address, draft: u32, colors: Map<Address, Map<u32, Vec<u32>>>);
+    fn draft_erase(env: Env, minter: Address, draft: u32, colors: Map<Address, Map<u32, Vec<u32>>>);
+    fn glyph_transfer(env: Env, to: Address, hash_type: HashType, spender: Option<Address>);
+    fn glyph_scrape(
+        env: Env,
+        to: Option<Address>,
+        hash_type: HashType,
+        spender: Option<Address>,
+        limit: Option<u32>,
+    ) -> ScrapeProgress;
+    fn glyph_scrape_status(env: Env, owner: Address, draft: u32) -> ScrapeProgress;
+    fn drafts_of(env: Env, owner: Address) -> Vec<u32>;
+    fn draft_recover_legacy(env: Env, owner: Address) -> u32;
     fn glyph_get(env: Env, hash_type: HashType) -> Result<GlyphType, Error>;
+    fn glyph_preview(
+        env: Env,
+        colors: Map<Address, Map<u32, Vec<u32>>>,
+        width: u32,
+        transparent: bool,
+    ) -> GlyphPreview;
+    fn glyph_minter(env: Env, hash: BytesN<32>) -> Option<Address>;
+    fn glyph_history(env: Env, hash: BytesN<32>, cursor: u32) -> Vec<ProvenanceEntry>;
+    fn glyph_transparent(env: Env, hash: BytesN<32>) -> bool;
+    fn glyph_canvas(env: Env) -> Canvas;
+    fn glyph_render(env: Env, hash: BytesN<32>, format: RenderFormat) -> Bytes;
+    fn glyphs_of(env: Env, owner: Address, cursor: u32, limit: u32) -> Vec<BytesN<32>>;
+    fn glyph_count(env: Env, owner: Address) -> u32;
+    fn glyph_index(env: Env, hash: BytesN<32>);
+    fn glyph_metadata(env: Env, hash: BytesN<32>) -> Option<GlyphMetadata>;
+    fn glyph_metadata_policy(env: Env, hash: BytesN<32>) -> MetadataPolicy;
+    fn glyph_metadata_update(env: Env, hash: BytesN<32>, metadata: GlyphMetadata, spender: Option<Address>);
+    fn glyph_metadata_set_policy(env: Env, hash: BytesN<32>, policy: MetadataPolicy, spender: Option<Address>);
+}
+
+pub trait NonFungible {
+    fn owner_of(env: Env, token_id: BytesN<32>) -> Address;
+    fn balance(env: Env, owner: Address) -> u32;
+    fn transfer(env: Env, from: Address, to: Address, token_id: BytesN<32>);
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, token_id: BytesN<32>);
+    fn approve(
+        env: Env,
+        approver: Address,
+        approved: Address,
+        token_id: BytesN<32>,
+        live_until_ledger: u32,
+    );
+    fn approve_for_all(env: Env, owner: Address, operator: Address, live_until_ledger: u32);
+    fn get_approved(env: Env, token_id: BytesN<32>) -> Option<Address>;
+    fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool;
+    fn name(env: Env) -> String;
+    fn symbol(env: Env) -> String;
+    fn token_uri(env: Env, token_id: BytesN<32>) -> String;
 }
 
 pub trait Exchange {
-    fn offer_post(env: Env, sell: Offer, buy: Offer) -> Result<(), Error>;
-    fn offer_delete(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error>;
+    fn offer_post(env: Env, sell: Offer, buy: Offer, spender: Option<Address>) -> Result<(), Error>;
+    fn offer_delete(
+        env: Env,
+        sell: Offer,
+        buy: Option<Offer>,
+        spender: Option<Address>,
+    ) -> Result<(), Error>;
     fn offers_get(env: Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error>;
 }
@@ -5,9 +5,6 @@ Likely including unnecessary auth or at least auth arguments (use `.require_auth
 Consider implementing fewer optional arguments at the cost of argument duplication?
 Rethink bumps, many times (every time) these should be handled as separate ops vs within the executable
 Ensure we're appropriately using the 3 different storage types
-Implement events and ensure events aren't included in loops vs just single call events.
-    They need to exist, they don't need to be overly verbose or granular
-    Also make sure we're sending sufficient information. I think I saw a couple events not sending sufficient data to be useful to an indexer
 Ensure fully verifying necessary ownerships
 */
 
@@ -19,8 +16,19 @@ mod types;
 mod storage;
 mod events;
 
+mod colors;
 mod glyphs;
+mod history;
+mod metadata;
 mod offers;
+mod pricing;
+mod render;
+mod royalties;
+
+pub mod testutils;
+
+pub use contract::{ColorGlyph, ColorGlyphClient};
+pub use types::*;
 
 #[path = "./tests/colors.rs"]
 mod colors_test;
@@ -30,3 +38,7 @@ mod glyphs_test;
 // mod misc_test;
 #[path = "./tests/offers.rs"]
 mod offers_test;
+#[path = "./tests/nft.rs"]
+mod nft_test;
+#[path = "./tests/properties.rs"]
+mod properties_test;
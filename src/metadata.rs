@@ -0,0 +1,89 @@
+use soroban_sdk::{panic_with_error, Address, BytesN, Env};
+
+use crate::{
+    events::GlyphMetadataPolicyEvent,
+    glyphs::{glyph_verify_ownership, MAX_BASE_URI_SIZE},
+    storage::persistent::{
+        read_glyph_metadata_policy, read_glyph_minter, read_glyph_owner, remove_glyph_metadata,
+        remove_glyph_metadata_policy, write_glyph_metadata_policy,
+    },
+    types::{Error, GlyphMetadata, MetadataPolicy},
+};
+
+pub const MAX_TITLE_SIZE: u32 = 64;
+pub const MAX_STORY_SIZE: u32 = 1024;
+pub const MAX_ATTRIBUTE_COUNT: u32 = 16;
+pub const MAX_ATTRIBUTE_SIZE: u32 = 64;
+
+/* NOTE
+Metadata lives next to the glyph under its hash and goes away when the glyph is scraped
+Who can edit it, and change who can, is up to its `MetadataPolicy`, defaulting to `Owner`. `Frozen` can't be undone
+*/
+
+pub fn glyph_metadata_verify(env: &Env, metadata: &GlyphMetadata) {
+    if metadata.title.len() > MAX_TITLE_SIZE
+        || metadata.story.len() > MAX_STORY_SIZE
+        || metadata.external_url.len() > MAX_BASE_URI_SIZE as u32
+        || metadata.attributes.len() > MAX_ATTRIBUTE_COUNT
+    {
+        panic_with_error!(env, Error::MetadataTooLarge);
+    }
+
+    for (key, value) in metadata.attributes.iter() {
+        if key.len() > MAX_ATTRIBUTE_SIZE || value.len() > MAX_ATTRIBUTE_SIZE {
+            panic_with_error!(env, Error::MetadataTooLarge);
+        }
+    }
+}
+
+// Returns whoever is signing for the edit
+pub fn glyph_metadata_verify_editor(env: &Env, hash: &BytesN<32>, spender: &Option<Address>) -> Address {
+    match read_glyph_metadata_policy(env, hash.clone()) {
+        MetadataPolicy::Minter => {
+            // Scraped glyphs don't have metadata to edit
+            if read_glyph_owner(env, hash.clone()).is_none() {
+                panic_with_error!(env, Error::NotFound);
+            }
+
+            let minter = read_glyph_minter(env, hash.clone())
+                .unwrap_or_else(|| panic_with_error!(env, Error::NotFound));
+
+            minter.require_auth();
+            minter
+        }
+        MetadataPolicy::Owner | MetadataPolicy::FrozenOnSale => {
+            let owner = glyph_verify_ownership(env, hash, spender);
+
+            spender.clone().unwrap_or(owner)
+        }
+        MetadataPolicy::Frozen => panic_with_error!(env, Error::MetadataFrozen),
+    }
+}
+
+// Only whoever the current policy lets edit can change it, so an owner can't undo a `Minter` policy
+pub fn glyph_metadata_set_policy(env: &Env, hash: &BytesN<32>, policy: &MetadataPolicy, spender: &Option<Address>) {
+    let editor = glyph_metadata_verify_editor(env, hash, spender);
+
+    write_glyph_metadata_policy(env, hash.clone(), policy);
+
+    crate::events::glyph_metadata_policy(
+        env,
+        GlyphMetadataPolicyEvent {
+            hash: hash.clone(),
+            editor,
+            policy: *policy,
+        },
+    );
+}
+
+// Called on every ownership change that comes out of an offer being taken
+pub fn glyph_metadata_sold(env: &Env, hash: &BytesN<32>) {
+    if read_glyph_metadata_policy(env, hash.clone()) == MetadataPolicy::FrozenOnSale {
+        write_glyph_metadata_policy(env, hash.clone(), &MetadataPolicy::Frozen);
+    }
+}
+
+pub fn glyph_metadata_remove(env: &Env, hash: &BytesN<32>) {
+    remove_glyph_metadata(env, hash.clone());
+    remove_glyph_metadata_policy(env, hash.clone());
+}
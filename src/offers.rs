@@ -1,12 +1,19 @@
 // use std::println;
 // extern crate std;
 
-use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{token, vec, Address, Env, Symbol, Vec};
+use soroban_sdk::{token, vec, Address, BytesN, Env, Vec};
 
 use crate::{
-    glyphs::glyph_verify_ownership,
-    types::{Error, Glyph, Offer, OfferCreate, StorageKey},
+    events::{OfferCancelEvent, OfferFillEvent, OfferPostEvent},
+    glyphs::{glyph_is_approved, glyph_set_owner, glyph_verify_ownership},
+    history::glyph_history_add,
+    metadata::glyph_metadata_sold,
+    royalties::royalties_pay,
+    storage::persistent::{
+        read_glyph_offer_spender, read_glyph_owner, remove_glyph_offer, remove_glyph_offer_spender,
+        write_glyph_offer_spender,
+    },
+    types::{Error, Offer, OfferCreate, Provenance, StorageKey},
 };
 
 /* TODO
@@ -23,12 +30,20 @@ Place caps on the number of GlyphOffer and AssetOffer Vec lengths
     how many identical glyph:asset:amount offers can be open?
 */
 
-pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
+pub fn offer_post(env: &Env, sell: Offer, buy: Offer, spender: Option<Address>) -> Result<(), Error> {
     // sell glyph
     // lookup if someone is selling what you're buying
     // sell asset
     // lookup if someone is selling what you're buying
 
+    for offer in [&sell, &buy] {
+        if let Offer::Asset(_, amount) | Offer::AssetSell(_, _, amount) = offer {
+            if *amount <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+        }
+    }
+
     // Lookup if there are any open buy offers for what we're selling
     match &buy {
         // buying a glyph
@@ -46,14 +61,17 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
             //     MAX_ENTRY_LIFETIME,
             // );
 
-            match offers.binary_search(match &sell {
+            let sell_offer = match &sell {
                 Offer::Glyph(sell_glyph_hash) => Offer::Glyph(sell_glyph_hash.clone()),
                 Offer::AssetSell(_, sell_asset_address, amount) => {
                     Offer::Asset(sell_asset_address.clone(), *amount)
                 }
                 _ => return Err(Error::NotPermitted),
-            }) {
-                Ok(offer_index) => {
+            };
+
+            match offers.binary_search(&sell_offer) {
+                // A lapsed spender offer is as good as no offer, we post ours instead
+                Ok(offer_index) if offer_is_live(env, buy_glyph_hash, &sell_offer) => {
                     let buy_glyph_owner_key = StorageKey::GlyphOwner(buy_glyph_hash.clone());
                     let buy_glyph_owner_address = env
                         .storage()
@@ -75,22 +93,49 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
 
                     match &sell {
                         Offer::Glyph(sell_glyph_hash) => {
-                            let sell_glyph_offer_key =
-                                StorageKey::GlyphOffer(sell_glyph_hash.clone());
-                            let sell_glyph_owner_key =
-                                StorageKey::GlyphOwner(sell_glyph_hash.clone());
                             let sell_glyph_owner_address =
-                                glyph_verify_ownership(env, &sell_glyph_owner_key);
+                                glyph_verify_ownership(env, sell_glyph_hash, &spender);
+
+                            if sell_glyph_owner_address == buy_glyph_owner_address {
+                                return Err(Error::SelfTrade);
+                            }
 
                             // transfer ownership from seller to buyer
-                            env.storage()
-                                .persistent()
-                                .set(&sell_glyph_owner_key, &buy_glyph_owner_address);
+                            glyph_set_owner(
+                                env,
+                                sell_glyph_hash,
+                                Some(sell_glyph_owner_address.clone()),
+                                Some(buy_glyph_owner_address.clone()),
+                            );
+                            glyph_metadata_sold(env, sell_glyph_hash);
 
                             // transfer ownership from buyer to seller
-                            env.storage()
-                                .persistent()
-                                .set(&buy_glyph_owner_key, &sell_glyph_owner_address);
+                            glyph_set_owner(
+                                env,
+                                buy_glyph_hash,
+                                Some(buy_glyph_owner_address.clone()),
+                                Some(sell_glyph_owner_address.clone()),
+                            );
+                            glyph_metadata_sold(env, buy_glyph_hash);
+
+                            glyph_history_add(
+                                env,
+                                sell_glyph_hash,
+                                Provenance::Sale(
+                                    sell_glyph_owner_address.clone(),
+                                    buy_glyph_owner_address.clone(),
+                                    buy.clone(),
+                                ),
+                            );
+                            glyph_history_add(
+                                env,
+                                buy_glyph_hash,
+                                Provenance::Sale(
+                                    buy_glyph_owner_address.clone(),
+                                    sell_glyph_owner_address.clone(),
+                                    sell.clone(),
+                                ),
+                            );
 
                             // env.storage().persistent().bump(
                             //     &sell_glyph_owner_key,
@@ -103,19 +148,15 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                             //     MAX_ENTRY_LIFETIME,
                             // );
 
-                            // remove all glyph seller offers
-                            env.storage().persistent().remove(&sell_glyph_offer_key);
-
-                            // remove all glyph buyer offers
-                            env.storage().persistent().remove(&buy_glyph_offer_key);
-
-                            env.events().publish(
-                                (
-                                    Symbol::new(&env, "offer_match"),
-                                    sell_glyph_hash.clone(),
-                                    sell_glyph_owner_address,
-                                ),
-                                (buy_glyph_hash.clone(), buy_glyph_owner_address),
+                            crate::events::offer_fill(
+                                env,
+                                OfferFillEvent {
+                                    hash: buy_glyph_hash.clone(),
+                                    seller: buy_glyph_owner_address,
+                                    buyer: sell_glyph_owner_address,
+                                    price: sell.clone(),
+                                    proceeds: 0,
+                                },
                             );
 
                             Ok(())
@@ -123,105 +164,37 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                         Offer::AssetSell(sell_asset_owner_address, sell_asset_address, amount) => {
                             sell_asset_owner_address.require_auth();
 
-                            let buy_glyph_key = StorageKey::Glyph(buy_glyph_hash.clone());
-                            let buy_glyph_minter_key =
-                                StorageKey::GlyphMinter(buy_glyph_hash.clone());
-
-                            // TODO Might want to make a map of payees to reduce or eliminate piecemeal payments (e.g. overlap between minter and miner)
-                            let mut leftover_amount = *amount;
-
-                            // Get glyph
-                            let buy_glyph = env
-                                .storage()
-                                .persistent()
-                                .get::<StorageKey, Glyph>(&buy_glyph_key)
-                                .ok_or(Error::NotFound)?;
-                            let buy_glyph_minter_address = env
-                                .storage()
-                                .persistent()
-                                .get::<StorageKey, Address>(&buy_glyph_minter_key)
-                                .ok_or(Error::NotFound)?;
-
-                            // env.storage().persistent().bump(
-                            //     &buy_glyph_key,
-                            //     MAX_ENTRY_LIFETIME,
-                            //     MAX_ENTRY_LIFETIME,
-                            // );
-                            // env.storage().persistent().bump(
-                            //     &buy_glyph_minter_key,
-                            //     MAX_ENTRY_LIFETIME,
-                            //     MAX_ENTRY_LIFETIME,
-                            // );
-
-                            // Pay the glyph minter their cut
-                            let minter_royalty_rate = env
-                                .storage()
-                                .instance()
-                                .get::<StorageKey, i128>(&StorageKey::MinterRoyaltyRate)
-                                .unwrap();
-                            let minter_amount =
-                                minter_royalty_rate.fixed_mul_ceil(*amount, 100).unwrap();
-
-                            let token = token::Client::new(env, &sell_asset_address);
-
-                            if *sell_asset_owner_address != buy_glyph_minter_address {
-                                // eliminate self payments
-                                token.transfer(
-                                    &sell_asset_owner_address,
-                                    &buy_glyph_minter_address,
-                                    &minter_amount,
-                                );
-
-                                leftover_amount -= minter_amount;
+                            if *sell_asset_owner_address == buy_glyph_owner_address {
+                                return Err(Error::SelfTrade);
                             }
 
-                            // Loop over miners
-                            // NOTE currently can support 17 miners
-                            for (miner_address, colors_indexes) in buy_glyph.colors.iter() {
-                                let mut color_count: u32 = 0;
-
-                                // Count colors per miner
-                                for (_, indexes) in colors_indexes.iter() {
-                                    color_count += indexes.len();
-                                }
-
-                                let miner_royalty_rate = env
-                                    .storage()
-                                    .instance()
-                                    .get::<StorageKey, i128>(&StorageKey::MinerRoyaltyRate)
-                                    .unwrap();
-                                let miner_amount = miner_royalty_rate
-                                    .fixed_mul_ceil(*amount, 100)
-                                    .unwrap()
-                                    .fixed_mul_ceil(color_count as i128, buy_glyph.length as i128)
-                                    .unwrap();
-
-                                // Determine their percentage of whole
-                                // Derive their share of the amount
-                                // Make payment
-                                if *sell_asset_owner_address != miner_address {
-                                    token.transfer(
-                                        &sell_asset_owner_address,
-                                        &miner_address,
-                                        &miner_amount,
-                                    );
-                                    leftover_amount -= miner_amount;
-                                }
-                            }
-
-                            // Transfer Asset from Glyph taker to Glyph giver
-                            if *sell_asset_owner_address != buy_glyph_owner_address {
-                                token.transfer(
-                                    &sell_asset_owner_address,
-                                    &buy_glyph_owner_address,
-                                    &leftover_amount,
-                                );
-                            }
+                            let breakdown = royalties_pay(
+                                env,
+                                buy_glyph_hash,
+                                sell_asset_address,
+                                sell_asset_owner_address,
+                                sell_asset_owner_address,
+                                &buy_glyph_owner_address,
+                                *amount,
+                            )?;
 
                             // Transfer ownership of Glyph from glyph giver to Glyph taker
-                            env.storage()
-                                .persistent()
-                                .set(&buy_glyph_owner_key, &sell_asset_owner_address);
+                            glyph_set_owner(
+                                env,
+                                buy_glyph_hash,
+                                Some(buy_glyph_owner_address.clone()),
+                                Some(sell_asset_owner_address.clone()),
+                            );
+                            glyph_metadata_sold(env, buy_glyph_hash);
+                            glyph_history_add(
+                                env,
+                                buy_glyph_hash,
+                                Provenance::Sale(
+                                    buy_glyph_owner_address.clone(),
+                                    sell_asset_owner_address.clone(),
+                                    Offer::Asset(sell_asset_address.clone(), *amount),
+                                ),
+                            );
 
                             // env.storage().persistent().bump(
                             //     &buy_glyph_owner_key,
@@ -229,16 +202,15 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                             //     MAX_ENTRY_LIFETIME,
                             // );
 
-                            // remove all other sell offers for this glyph
-                            env.storage().persistent().remove(&buy_glyph_offer_key);
-
-                            env.events().publish(
-                                (
-                                    Symbol::new(&env, "offer_match"),
-                                    sell_asset_address,
-                                    sell_asset_owner_address,
-                                ),
-                                (*amount, buy_glyph_hash.clone(), offer_index),
+                            crate::events::offer_fill(
+                                env,
+                                OfferFillEvent {
+                                    hash: buy_glyph_hash.clone(),
+                                    seller: buy_glyph_owner_address,
+                                    buyer: sell_asset_owner_address.clone(),
+                                    price: Offer::Asset(sell_asset_address.clone(), *amount),
+                                    proceeds: breakdown.proceeds,
+                                },
                             );
 
                             Ok(())
@@ -248,7 +220,11 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                 }
                 _ => match &sell {
                     Offer::Glyph(sell_glyph_hash) => {
-                        offer_post_create(env, OfferCreate::Glyph(sell_glyph_hash.clone(), buy))
+                        offer_post_create(
+                            env,
+                            OfferCreate::Glyph(sell_glyph_hash.clone(), buy),
+                            &spender,
+                        )
                     }
                     Offer::AssetSell(sell_asset_owner_address, sell_asset_address, amount) => {
                         offer_post_create(
@@ -259,6 +235,7 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                                 sell_asset_address.clone(),
                                 *amount,
                             ),
+                            &spender,
                         )
                     }
                     _ => Err(Error::NotPermitted),
@@ -284,6 +261,7 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                         return offer_post_create(
                             env,
                             OfferCreate::Glyph(sell_glyph_hash.clone(), buy),
+                            &spender,
                         );
                     }
 
@@ -293,101 +271,25 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     //     MAX_ENTRY_LIFETIME,
                     // );
 
-                    let sell_glyph_owner_key = StorageKey::GlyphOwner(sell_glyph_hash.clone());
                     let sell_glyph_owner_address =
-                        glyph_verify_ownership(env, &sell_glyph_owner_key);
-                    let sell_glyph_minter_key = StorageKey::GlyphMinter(sell_glyph_hash.clone());
-                    let sell_glyph_offer_key = StorageKey::GlyphOffer(sell_glyph_hash.clone());
-                    let sell_glyph_key = StorageKey::Glyph(sell_glyph_hash.clone());
-
-                    /* TODO
-                    This next block is essentially a dupe from up above, this should be broken up into a separate function
-                    Might want to make a map of payees to reduce or eliminate piecemeal payments (e.g. overlap between minter and miner)
-                    */
-                    let mut leftover_amount = *amount;
-
-                    // Get glyph
-                    let sell_glyph = env
-                        .storage()
-                        .persistent()
-                        .get::<StorageKey, Glyph>(&sell_glyph_key)
-                        .ok_or(Error::NotFound)?;
-                    let sell_glyph_minter = env
-                        .storage()
-                        .persistent()
-                        .get::<StorageKey, Address>(&sell_glyph_minter_key)
-                        .ok_or(Error::NotFound)?;
-
-                    // env.storage().persistent().bump(
-                    //     &sell_glyph_key,
-                    //     MAX_ENTRY_LIFETIME,
-                    //     MAX_ENTRY_LIFETIME,
-                    // );
-                    // env.storage().persistent().bump(
-                    //     &sell_glyph_minter_key,
-                    //     MAX_ENTRY_LIFETIME,
-                    //     MAX_ENTRY_LIFETIME,
-                    // );
-
-                    // Pay the glyph minter their cut
-                    let minter_royalty_rate = env
-                        .storage()
-                        .instance()
-                        .get::<StorageKey, i128>(&StorageKey::MinterRoyaltyRate)
-                        .unwrap();
-                    let minter_amount = minter_royalty_rate.fixed_mul_ceil(*amount, 100).unwrap();
-                    let token = token::Client::new(env, &buy_asset_address);
-
-                    token.transfer(
-                        &env.current_contract_address(),
-                        &sell_glyph_minter,
-                        &minter_amount,
-                    );
-
-                    leftover_amount -= minter_amount;
-
-                    // Loop over miners
-                    // NOTE currently can support 15 miners
-                    for (miner_address, colors_indexes) in sell_glyph.colors.iter() {
-                        let mut color_count: u32 = 0;
-
-                        // Count colors per miner
-                        for (_, indexes) in colors_indexes.iter() {
-                            color_count += indexes.len();
-                        }
+                        glyph_verify_ownership(env, sell_glyph_hash, &spender);
 
-                        let miner_royalty_rate = env
-                            .storage()
-                            .instance()
-                            .get::<StorageKey, i128>(&StorageKey::MinerRoyaltyRate)
-                            .unwrap();
-                        let miner_amount = miner_royalty_rate
-                            .fixed_mul_ceil(*amount, 100)
-                            .unwrap()
-                            .fixed_mul_ceil(color_count as i128, sell_glyph.length as i128)
-                            .unwrap();
-
-                        // Determine their percentage of whole
-                        // Derive their share of the amount
-                        // Make payment
-                        token.transfer(
-                            &env.current_contract_address(),
-                            &miner_address,
-                            &miner_amount,
-                        );
+                    // Remove Asset counter offer
+                    let buy_asset_owner = offers.pop_front().ok_or(Error::OfferNotFound)?;
 
-                        leftover_amount -= miner_amount;
+                    if buy_asset_owner == sell_glyph_owner_address {
+                        return Err(Error::SelfTrade);
                     }
 
-                    // Transfer Asset from Glyph taker to Glyph giver
-                    token.transfer(
+                    let breakdown = royalties_pay(
+                        env,
+                        sell_glyph_hash,
+                        buy_asset_address,
                         &env.current_contract_address(),
+                        &buy_asset_owner,
                         &sell_glyph_owner_address,
-                        &leftover_amount,
-                    );
-
-                    // Remove Asset counter offer
-                    let buy_asset_owner = offers.pop_front().unwrap();
+                        *amount,
+                    )?;
 
                     if offers.is_empty() {
                         env.storage().persistent().remove(&buy_asset_offer_key);
@@ -404,9 +306,18 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     }
 
                     // Transfer ownership of Glyph from Glyph giver to Glyph taker
-                    env.storage()
-                        .persistent()
-                        .set(&sell_glyph_owner_key, &buy_asset_owner);
+                    glyph_set_owner(
+                        env,
+                        sell_glyph_hash,
+                        Some(sell_glyph_owner_address.clone()),
+                        Some(buy_asset_owner.clone()),
+                    );
+                    glyph_metadata_sold(env, sell_glyph_hash);
+                    glyph_history_add(
+                        env,
+                        sell_glyph_hash,
+                        Provenance::Sale(sell_glyph_owner_address.clone(), buy_asset_owner.clone(), buy.clone()),
+                    );
 
                     // env.storage().persistent().bump(
                     //     &sell_glyph_owner_key,
@@ -414,16 +325,15 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
                     //     MAX_ENTRY_LIFETIME,
                     // );
 
-                    // Remove all other sell offers for this glyph
-                    env.storage().persistent().remove(&sell_glyph_offer_key);
-
-                    env.events().publish(
-                        (
-                            Symbol::new(&env, "offer_post"),
-                            buy_asset_address,
-                            buy_asset_owner,
-                        ),
-                        (*amount, sell_glyph_hash.clone()),
+                    crate::events::offer_fill(
+                        env,
+                        OfferFillEvent {
+                            hash: sell_glyph_hash.clone(),
+                            seller: sell_glyph_owner_address,
+                            buyer: buy_asset_owner,
+                            price: buy.clone(),
+                            proceeds: breakdown.proceeds,
+                        },
                     );
 
                     Ok(())
@@ -435,13 +345,18 @@ pub fn offer_post(env: &Env, sell: Offer, buy: Offer) -> Result<(), Error> {
     }
 }
 
-fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
+fn offer_post_create(env: &Env, offer: OfferCreate, spender: &Option<Address>) -> Result<(), Error> {
     match offer {
         OfferCreate::Glyph(sell_glyph_hash, buy) => {
-            let sell_glyph_owner_key = StorageKey::GlyphOwner(sell_glyph_hash.clone());
-            let sell_glyph_owner_address = glyph_verify_ownership(env, &sell_glyph_owner_key);
+            let sell_glyph_owner_address = glyph_verify_ownership(env, &sell_glyph_hash, spender);
             let sell_glyph_offer_key = StorageKey::GlyphOffer(sell_glyph_hash.clone());
 
+            if let Offer::Glyph(buy_glyph_hash) = &buy {
+                if read_glyph_owner(env, buy_glyph_hash.clone()) == Some(sell_glyph_owner_address.clone()) {
+                    return Err(Error::SelfTrade);
+                }
+            }
+
             // Selling a Glyph
             let mut offers = env
                 .storage()
@@ -451,26 +366,34 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
 
             match offers.binary_search(&buy) {
                 Err(offer_index) => offers.insert(offer_index, buy.clone()), // Buy can be an Asset or a Glyph
-                _ => return Err(Error::NotEmpty), // Error on dupe offer
+                _ => return Err(Error::OfferExists),
             }
 
             env.storage()
                 .persistent()
                 .set(&sell_glyph_offer_key, &offers);
 
+            match spender {
+                Some(spender) if *spender != sell_glyph_owner_address => {
+                    write_glyph_offer_spender(env, sell_glyph_hash.clone(), buy.clone(), spender)
+                }
+                _ => remove_glyph_offer_spender(env, sell_glyph_hash.clone(), buy.clone()),
+            }
+
             // env.storage().persistent().bump(
             //     &sell_glyph_offer_key,
             //     MAX_ENTRY_LIFETIME,
             //     MAX_ENTRY_LIFETIME,
             // );
 
-            env.events().publish(
-                (
-                    Symbol::new(&env, "offer_post"),
-                    sell_glyph_hash,
-                    sell_glyph_owner_address,
-                ),
-                buy,
+            crate::events::offer_post(
+                env,
+                &sell_glyph_hash,
+                OfferPostEvent {
+                    owner: sell_glyph_owner_address,
+                    sell: Offer::Glyph(sell_glyph_hash.clone()),
+                    buy,
+                },
             );
 
             Ok(())
@@ -485,6 +408,10 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
 
             sell_asset_owner_address.require_auth();
 
+            if read_glyph_owner(env, buy_glyph_hash.clone()) == Some(sell_asset_owner_address.clone()) {
+                return Err(Error::SelfTrade);
+            }
+
             token.transfer(
                 &sell_asset_owner_address,
                 &env.current_contract_address(),
@@ -501,7 +428,7 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
                 .unwrap_or(Vec::new(env));
 
             if offers.contains(sell_asset_owner_address.clone()) {
-                return Err(Error::NotEmpty); // Error on dupe offer
+                return Err(Error::OfferExists);
             }
 
             offers.push_back(sell_asset_owner_address.clone());
@@ -516,13 +443,14 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
             //     MAX_ENTRY_LIFETIME,
             // );
 
-            env.events().publish(
-                (
-                    Symbol::new(&env, "offer_post"),
-                    sell_asset_address,
-                    sell_asset_owner_address,
-                ),
-                (amount, buy_glyph_hash),
+            crate::events::offer_post(
+                env,
+                &buy_glyph_hash,
+                OfferPostEvent {
+                    owner: sell_asset_owner_address.clone(),
+                    sell: Offer::AssetSell(sell_asset_owner_address, sell_asset_address, amount),
+                    buy: Offer::Glyph(buy_glyph_hash.clone()),
+                },
             );
 
             Ok(())
@@ -530,19 +458,23 @@ fn offer_post_create(env: &Env, offer: OfferCreate) -> Result<(), Error> {
     }
 }
 
-pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Error> {
+pub fn offer_delete(
+    env: &Env,
+    sell: Offer,
+    buy: Option<Offer>,
+    spender: Option<Address>,
+) -> Result<(), Error> {
     match sell {
         Offer::Glyph(glyph_hash) => {
             // Selling a Glyph (delete Glyph or Asset buy offer)
-            let glyph_owner_key = StorageKey::GlyphOwner(glyph_hash.clone());
-            let glyph_owner = glyph_verify_ownership(env, &glyph_owner_key);
+            let glyph_owner = glyph_verify_ownership(env, &glyph_hash, &spender);
 
             let glyph_hash_key = StorageKey::GlyphOffer(glyph_hash.clone());
             let mut offers = env
                 .storage()
                 .persistent()
                 .get::<StorageKey, Vec<Offer>>(&glyph_hash_key)
-                .ok_or(Error::NotFound)?;
+                .ok_or(Error::OfferNotFound)?;
 
             // env.storage().persistent().bump(
             //     &glyph_hash_key,
@@ -556,6 +488,7 @@ pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Er
                         offers.remove(offer_index);
 
                         env.storage().persistent().set(&glyph_hash_key, &offers);
+                        remove_glyph_offer_spender(env, glyph_hash.clone(), buy.clone());
 
                         // env.storage().persistent().bump(
                         //     &glyph_hash_key,
@@ -563,21 +496,31 @@ pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Er
                         //     MAX_ENTRY_LIFETIME,
                         // );
 
-                        env.events().publish(
-                            (Symbol::new(&env, "offer_delete"), glyph_hash, glyph_owner),
-                            (buy.clone(), offer_index),
+                        crate::events::offer_cancel(
+                            env,
+                            &glyph_hash,
+                            OfferCancelEvent {
+                                owner: glyph_owner,
+                                sell: Offer::Glyph(glyph_hash.clone()),
+                                buys: vec![env, buy.clone()],
+                            },
                         );
 
                         Ok(())
                     }
-                    _ => Err(Error::NotFound),
+                    _ => Err(Error::OfferNotFound),
                 },
                 None => {
-                    env.storage().persistent().remove(&glyph_hash_key);
-
-                    env.events().publish(
-                        (Symbol::new(&env, "offer_delete"), glyph_hash, glyph_owner),
-                        buy.clone(),
+                    remove_glyph_offer(env, glyph_hash.clone());
+
+                    crate::events::offer_cancel(
+                        env,
+                        &glyph_hash,
+                        OfferCancelEvent {
+                            owner: glyph_owner,
+                            sell: Offer::Glyph(glyph_hash.clone()),
+                            buys: offers,
+                        },
                     );
 
                     Ok(())
@@ -601,7 +544,7 @@ pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Er
                                 .storage()
                                 .persistent()
                                 .get::<StorageKey, Vec<Address>>(&asset_offer_key)
-                                .ok_or(Error::NotFound)?;
+                                .ok_or(Error::OfferNotFound)?;
 
                             // env.storage().persistent().bump(
                             //     &asset_offer_key,
@@ -621,13 +564,14 @@ pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Er
 
                                     offers.remove(offer_index);
 
-                                    env.events().publish(
-                                        (
-                                            Symbol::new(&env, "offer_delete"),
-                                            asset_address,
-                                            asset_owner_address,
-                                        ),
-                                        (amount, glyph_hash, offer_index),
+                                    crate::events::offer_cancel(
+                                        env,
+                                        &glyph_hash,
+                                        OfferCancelEvent {
+                                            owner: asset_owner_address.clone(),
+                                            sell: Offer::AssetSell(asset_owner_address, asset_address, amount),
+                                            buys: vec![env, Offer::Glyph(glyph_hash.clone())],
+                                        },
                                     );
 
                                     if offers.is_empty() {
@@ -644,7 +588,7 @@ pub fn offer_delete(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Er
 
                                     Ok(())
                                 }
-                                _ => Err(Error::NotFound),
+                                _ => Err(Error::OfferNotFound),
                             }
                         }
                         _ => Err(Error::NotPermitted), // You cannot sell an Asset for an Asset
@@ -666,7 +610,7 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
                 .storage()
                 .persistent()
                 .get::<StorageKey, Vec<Offer>>(&glyph_hash_key)
-                .ok_or(Error::NotFound)?;
+                .ok_or(Error::OfferNotFound)?;
 
             // env.storage().persistent().bump(
             //     &glyph_hash_key,
@@ -675,9 +619,9 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
             // );
 
             match buy {
-                Some(buy) => match offers.binary_search(buy) {
-                    Ok(_) => Ok(()), // Found the buy offer
-                    _ => Err(Error::NotFound),
+                Some(buy) => match offers.binary_search(&buy) {
+                    Ok(_) if offer_is_live(env, &glyph_hash, &buy) => Ok(()), // Found the buy offer
+                    _ => Err(Error::OfferNotFound),
                 },
                 _ => Ok(()), // There are buy offers for this Glyph
             }
@@ -696,7 +640,7 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
                             env.storage()
                                 .persistent()
                                 .get::<StorageKey, Vec<Address>>(&asset_offer_key)
-                                .ok_or(Error::NotFound)?;
+                                .ok_or(Error::OfferNotFound)?;
 
                             // env.storage().persistent().bump(
                             //     &asset_offer_key,
@@ -727,7 +671,7 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
                                 .storage()
                                 .persistent()
                                 .get::<StorageKey, Vec<Address>>(&asset_offer_key)
-                                .ok_or(Error::NotFound)?;
+                                .ok_or(Error::OfferNotFound)?;
 
                             if offers.contains(seller_address) {
                                 // env.storage().persistent().bump(
@@ -739,7 +683,7 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
                                 return Ok(());
                             }
 
-                            Err(Error::NotFound)
+                            Err(Error::OfferNotFound)
                         }
                         _ => Err(Error::NotPermitted), // You cannot sell an Asset for an Asset
                     }
@@ -749,3 +693,12 @@ pub fn offers_get(env: &Env, sell: Offer, buy: Option<Offer>) -> Result<(), Erro
         }
     }
 }
+
+// Sell offers the owner posted always stand, ones a spender posted lapse with the spender's approval
+fn offer_is_live(env: &Env, hash: &BytesN<32>, buy: &Offer) -> bool {
+    match read_glyph_offer_spender(env, hash.clone(), buy.clone()) {
+        Some(spender) => read_glyph_owner(env, hash.clone())
+            .is_some_and(|owner| glyph_is_approved(env, &owner, &spender, hash)),
+        None => true,
+    }
+}
@@ -0,0 +1,252 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, Address, Env, Map, Vec};
+
+use crate::{
+    storage::{
+        instance::{read_burn_refund_rate, read_mine_price, read_price_curve},
+        persistent::{read_color_cost, read_color_supply, write_color_cost, write_color_supply},
+    },
+    types::{Error, MinePrice, PriceCurve},
+};
+
+pub const SCALAR_7: i128 = 10_000_000;
+pub const MAX_CURVE_GROWTH: i128 = 1_000 * SCALAR_7; // exponential unit prices stop compounding at 1000x
+
+/* NOTE
+Mining is charged per pixel in the configured token
+`base_price * color multiplier` per color, moved along the price curve by how much of that color has ever been mined, then the best volume discount the total pixel count qualifies for
+Everything rounds in the protocol's favor
+*/
+
+pub fn mine_quote(env: &Env, colors: &Map<u32, u32>) -> i128 {
+    mine_prices(env, colors).0
+}
+
+// The total price plus what each color cost after the discount
+// Per color costs round down so together they never add up to more than was paid
+pub fn mine_prices(env: &Env, colors: &Map<u32, u32>) -> (i128, Map<u32, i128>) {
+    let mine_price = read_mine_price(env);
+    let price_curve = read_price_curve(env);
+
+    let mut pixel_count: u32 = 0;
+    let mut price: i128 = 0;
+    let mut color_prices: Map<u32, i128> = Map::new(env);
+
+    for (color, amount) in colors.iter() {
+        let multiplier = mine_price.color_multipliers.get(color).unwrap_or(100);
+        let supply = read_color_supply(env, color);
+
+        // Priced in hundredths so the multiplier only gets rounded once
+        let color_price = mine_price
+            .base_price
+            .checked_mul(multiplier as i128)
+            .and_then(|unit_price| curve_sum(&price_curve, unit_price, supply, amount))
+            .and_then(|color_price| color_price.fixed_mul_ceil(1, 100))
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice));
+
+        pixel_count = pixel_count
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidAmount));
+        price = price
+            .checked_add(color_price)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice));
+
+        color_prices.set(color, color_price);
+    }
+
+    let mut discount: u32 = 0;
+
+    for (min_pixel_count, percent) in mine_price.volume_discounts.iter() {
+        if pixel_count < min_pixel_count {
+            break;
+        }
+
+        discount = percent;
+    }
+
+    for (color, color_price) in color_prices.iter() {
+        color_prices.set(
+            color,
+            color_price
+                .fixed_mul_floor(100 - discount as i128, 100)
+                .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice)),
+        );
+    }
+
+    let price = price
+        .fixed_mul_ceil(100 - discount as i128, 100)
+        .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice));
+
+    (price, color_prices)
+}
+
+/* NOTE
+Burn refunds are a cut of what was actually paid to mine the burned units, never of the current schedule
+`ColorCost(miner, color)` keeps what's been paid for that miner's units of a color still around, a burn takes its average share out whether or not there's a refund
+So lowering the price or discounting a mine can't be turned into a refund worth more than it cost
+Units mined before the cost was tracked have nothing paid against them and refund nothing
+*/
+
+pub fn mine_cost_add(env: &Env, miner: &Address, colors: &Map<u32, u32>, color_prices: &Map<u32, i128>) {
+    for (color, amount) in colors.iter() {
+        let (paid, units) = read_color_cost(env, miner.clone(), color);
+
+        write_color_cost(
+            env,
+            miner.clone(),
+            color,
+            (paid + color_prices.get(color).unwrap_or(0), units + amount as u64),
+        );
+    }
+}
+
+// Takes the burned units out of their miners' costs and refunds `BurnRefundRate` percent of it
+// Rounds down as it's paid out of the treasury
+pub fn burn_refund(env: &Env, colors: &Vec<(Address, u32, u32)>) -> i128 {
+    let mut paid_burned: i128 = 0;
+
+    for (miner, color, amount) in colors.iter() {
+        let (paid, units) = read_color_cost(env, miner.clone(), color);
+        let burned_units = units.min(amount as u64);
+
+        if burned_units == 0 {
+            continue;
+        }
+
+        let burned_paid = paid
+            .fixed_mul_floor(burned_units as i128, units as i128)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice));
+
+        write_color_cost(env, miner, color, (paid - burned_paid, units - burned_units));
+
+        paid_burned += burned_paid;
+    }
+
+    paid_burned
+        .fixed_mul_floor(read_burn_refund_rate(env), 100)
+        .unwrap_or_else(|| panic_with_error!(env, Error::InvalidPrice))
+}
+
+pub fn mine_supply_add(env: &Env, colors: &Map<u32, u32>) {
+    for (color, amount) in colors.iter() {
+        let supply = read_color_supply(env, color);
+
+        write_color_supply(env, color, supply + amount as u64);
+    }
+}
+
+// Sum of the unit prices for units `supply..supply + amount`
+fn curve_sum(price_curve: &PriceCurve, unit_price: i128, supply: u64, amount: u32) -> Option<i128> {
+    let amount = amount as i128;
+    let supply = supply as i128;
+
+    match price_curve {
+        PriceCurve::Flat => unit_price.checked_mul(amount),
+        // unit_price * (amount + rate * (supply * amount + amount * (amount - 1) / 2))
+        PriceCurve::Linear(rate) => {
+            let steps = supply
+                .checked_mul(amount)?
+                .checked_add(amount * (amount - 1) / 2)?;
+
+            unit_price
+                .checked_mul(amount)?
+                .checked_add(unit_price.checked_mul(steps)?.fixed_mul_ceil(*rate, SCALAR_7)?)
+        }
+        // unit_price * r^supply * (r^amount - 1) / (r - 1) up to the cap, then unit_price * r^cap for every unit past it
+        PriceCurve::Exponential(rate) => {
+            if *rate == 0 {
+                return unit_price.checked_mul(amount);
+            }
+
+            let growth = SCALAR_7 + rate;
+            let cap_supply = curve_cap_supply(growth);
+            let growing = (cap_supply - supply).clamp(0, amount);
+
+            let growing_price = if growing > 0 {
+                unit_price
+                    .fixed_mul_ceil(fixed_pow(growth, supply)?, SCALAR_7)?
+                    .fixed_mul_ceil(fixed_pow(growth, growing)? - SCALAR_7, *rate)?
+            } else {
+                0
+            };
+
+            unit_price
+                .fixed_mul_ceil(fixed_pow(growth, cap_supply)?, SCALAR_7)?
+                .checked_mul(amount - growing)?
+                .checked_add(growing_price)
+        }
+    }
+}
+
+// How many units it takes an exponential curve to reach `MAX_CURVE_GROWTH`, so no supply can overflow it
+fn curve_cap_supply(growth: i128) -> i128 {
+    let capped = |exponent: i128| fixed_pow(growth, exponent).map_or(true, |factor| factor >= MAX_CURVE_GROWTH);
+
+    let mut high: i128 = 1;
+
+    while !capped(high) {
+        high *= 2;
+    }
+
+    let mut low = high / 2;
+
+    // `low` is under the cap (or 0), `high` is at or over it
+    while high - low > 1 {
+        let middle = (low + high) / 2;
+
+        if capped(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    high
+}
+
+fn fixed_pow(base: i128, exponent: i128) -> Option<i128> {
+    let mut base = base;
+    let mut exponent = exponent;
+    let mut result = SCALAR_7;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.fixed_mul_ceil(base, SCALAR_7)?;
+        }
+
+        exponent >>= 1;
+
+        if exponent > 0 {
+            base = base.fixed_mul_ceil(base, SCALAR_7)?;
+        }
+    }
+
+    Some(result)
+}
+
+pub fn mine_price_verify(env: &Env, mine_price: &MinePrice) {
+    if mine_price.base_price < 0 {
+        panic_with_error!(env, Error::InvalidPrice);
+    }
+
+    let mut last_min_pixel_count: Option<u32> = None;
+
+    for (min_pixel_count, percent) in mine_price.volume_discounts.iter() {
+        if percent > 100 || last_min_pixel_count.is_some_and(|last| min_pixel_count <= last) {
+            panic_with_error!(env, Error::InvalidPrice);
+        }
+
+        last_min_pixel_count = Some(min_pixel_count);
+    }
+}
+
+pub fn price_curve_verify(env: &Env, price_curve: &PriceCurve) {
+    match price_curve {
+        PriceCurve::Flat => {}
+        PriceCurve::Linear(rate) | PriceCurve::Exponential(rate) => {
+            if *rate < 0 {
+                panic_with_error!(env, Error::InvalidRate);
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use soroban_sdk::{Bytes, Env, String};
+
+use crate::{
+    contract::MAX_PIXELS,
+    types::{Glyph, RenderFormat},
+};
+
+pub const MAX_RECT_SIZE: usize = 64; // <rect x="4095" y="4095" width="4096" height="1" fill="#ffffff"/>
+pub const MAX_SVG_SIZE: usize = 160 + MAX_PIXELS * MAX_RECT_SIZE;
+
+const SVG_DATA_URI: &[u8] = b"data:image/svg+xml;base64,";
+const MAX_DATA_URI_SIZE: usize = 26 + MAX_SVG_SIZE.div_ceil(3) * 4;
+
+/* NOTE
+Renders straight from the stored `Glyph` so no client needs its own renderer
+Pixels that were never colored render white, same as they're hashed in `glyph_store`, unless the glyph was minted transparent
+SVGs draw a white background (if opaque) and then one rect per horizontal run of the same color
+*/
+
+// Everything renders through a `Sink` into host `Bytes`, the only linear memory used is sized to the glyph rather than the canvas
+trait Sink {
+    fn bytes(&mut self, bytes: &[u8]);
+
+    fn number(&mut self, number: u32) {
+        let mut digits = [0u8; 10];
+        let mut i = digits.len();
+        let mut number = number;
+
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (number % 10) as u8;
+            number /= 10;
+
+            if number == 0 {
+                break;
+            }
+        }
+
+        self.bytes(&digits[i..]);
+    }
+
+    fn hex_color(&mut self, color: u32) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+
+        self.bytes(b"#");
+
+        for shift in [20, 16, 12, 8, 4, 0] {
+            self.bytes(&[HEX[((color >> shift) & 0xf) as usize]]);
+        }
+    }
+
+    fn u16_le(&mut self, number: u16) {
+        self.bytes(&number.to_le_bytes());
+    }
+
+    fn u32_le(&mut self, number: u32) {
+        self.bytes(&number.to_le_bytes());
+    }
+}
+
+// Batches writes into host `Bytes`
+struct BytesSink {
+    bytes: Bytes,
+    chunk: [u8; 256],
+    len: usize,
+}
+
+impl BytesSink {
+    fn new(env: &Env) -> Self {
+        BytesSink {
+            bytes: Bytes::new(env),
+            chunk: [0; 256],
+            len: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        self.bytes.extend_from_slice(&self.chunk[..self.len]);
+        self.len = 0;
+    }
+
+    fn finish(mut self) -> Bytes {
+        self.flush();
+        self.bytes
+    }
+}
+
+impl Sink for BytesSink {
+    fn bytes(&mut self, bytes: &[u8]) {
+        if self.len + bytes.len() > self.chunk.len() {
+            self.flush();
+        }
+
+        self.chunk[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+}
+
+// Base64 encodes into host `Bytes` through a `BytesSink`
+struct Base64Sink {
+    out: BytesSink,
+    carry: [u8; 3],
+    carry_len: usize,
+}
+
+impl Base64Sink {
+    const BASE64: &'static [u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    fn new(out: BytesSink) -> Self {
+        Base64Sink {
+            out,
+            carry: [0; 3],
+            carry_len: 0,
+        }
+    }
+
+    fn encode(&mut self) {
+        let n = (self.carry[0] as u32) << 16 | (self.carry[1] as u32) << 8 | self.carry[2] as u32;
+
+        self.out.bytes(&[
+            Self::BASE64[(n >> 18 & 63) as usize],
+            Self::BASE64[(n >> 12 & 63) as usize],
+            if self.carry_len > 1 { Self::BASE64[(n >> 6 & 63) as usize] } else { b'=' },
+            if self.carry_len > 2 { Self::BASE64[(n & 63) as usize] } else { b'=' },
+        ]);
+
+        self.carry = [0; 3];
+        self.carry_len = 0;
+    }
+
+    fn finish(mut self) -> Bytes {
+        if self.carry_len > 0 {
+            self.encode();
+        }
+
+        self.out.finish()
+    }
+}
+
+impl Sink for Base64Sink {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.carry[self.carry_len] = *byte;
+            self.carry_len += 1;
+
+            if self.carry_len == 3 {
+                self.encode();
+            }
+        }
+    }
+}
+
+// Runs `f` on `len` items of the smallest buffer that fits so small glyphs don't pay for a full canvas
+fn with_buffer<T: Copy, R, const MAX: usize>(len: usize, fill: T, f: impl FnOnce(&mut [T]) -> R) -> R {
+    match len {
+        0..=64 => sized_buffer::<T, R, 64>(len, fill, f),
+        65..=512 => sized_buffer::<T, R, 512>(len, fill, f),
+        513..=4_096 => sized_buffer::<T, R, 4_096>(len, fill, f),
+        4_097..=32_768 => sized_buffer::<T, R, 32_768>(len, fill, f),
+        _ => sized_buffer::<T, R, MAX>(len, fill, f),
+    }
+}
+
+// Kept out of line so only the buffer actually picked takes up stack
+#[inline(never)]
+fn sized_buffer<T: Copy, R, const N: usize>(len: usize, fill: T, f: impl FnOnce(&mut [T]) -> R) -> R {
+    let mut buf = [fill; N];
+
+    f(&mut buf[..len])
+}
+
+const TRANSPARENT: u32 = u32::MAX;
+
+// Lays the glyph out in a buffer just big enough for its rows and hands it to `f` with the height
+fn glyph_pixels<R>(glyph: &Glyph, transparent: bool, f: impl FnOnce(&[u32], u32) -> R) -> R {
+    // Stored glyphs always have a width but there's no sense dividing by zero over it
+    let height = if glyph.width == 0 { 0 } else { glyph.length.div_ceil(glyph.width) };
+    let background = if transparent { TRANSPARENT } else { 0xffffff };
+
+    with_buffer::<_, _, MAX_PIXELS>((glyph.width * height) as usize, background, |pixels| {
+        for (_, color_indexes) in glyph.colors.iter() {
+            for (color, indexes) in color_indexes.iter() {
+                for index in indexes.iter() {
+                    if let Some(pixel) = pixels.get_mut(index as usize) {
+                        *pixel = color & 0xffffff;
+                    }
+                }
+            }
+        }
+
+        f(pixels, height)
+    })
+}
+
+fn svg_write(glyph: &Glyph, transparent: bool, writer: &mut impl Sink) {
+    glyph_pixels(glyph, transparent, |pixels, height| svg_pixels_write(glyph, transparent, pixels, height, writer));
+}
+
+fn svg_pixels_write(glyph: &Glyph, transparent: bool, pixels: &[u32], height: u32, writer: &mut impl Sink) {
+    // Uncolored pixels are either covered by this background or left out entirely
+    let background = if transparent { TRANSPARENT } else { 0xffffff };
+
+    writer.bytes(b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 ");
+    writer.number(glyph.width);
+    writer.bytes(b" ");
+    writer.number(height);
+    writer.bytes(b"\" shape-rendering=\"crispEdges\">");
+
+    if !transparent {
+        writer.bytes(b"<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>");
+    }
+
+    for y in 0..height {
+        let mut x = 0;
+
+        while x < glyph.width {
+            let color = pixels[(y * glyph.width + x) as usize];
+            let mut run = 1;
+
+            while x + run < glyph.width && pixels[(y * glyph.width + x + run) as usize] == color {
+                run += 1;
+            }
+
+            if color != background {
+                writer.bytes(b"<rect x=\"");
+                writer.number(x);
+                writer.bytes(b"\" y=\"");
+                writer.number(y);
+                writer.bytes(b"\" width=\"");
+                writer.number(run);
+                writer.bytes(b"\" height=\"1\" fill=\"");
+                writer.hex_color(color);
+                writer.bytes(b"\"/>");
+            }
+
+            x += run;
+        }
+    }
+
+    writer.bytes(b"</svg>");
+}
+
+/* NOTE
+Opaque glyphs are 24 bit uncompressed BMPs, rows stored bottom up in BGR and padded to 4 bytes
+Transparent glyphs need an alpha channel so they're 32 bit BGRA with a V4 header and bitfield masks
+*/
+fn bmp_write(glyph: &Glyph, transparent: bool, writer: &mut impl Sink) {
+    glyph_pixels(glyph, transparent, |pixels, height| bmp_pixels_write(glyph, transparent, pixels, height, writer));
+}
+
+fn bmp_pixels_write(glyph: &Glyph, transparent: bool, pixels: &[u32], height: u32, writer: &mut impl Sink) {
+    let (header_size, bit_count, row_size) = if transparent {
+        (108, 32, glyph.width * 4)
+    } else {
+        (40, 24, (glyph.width * 3 + 3) & !3)
+    };
+    let image_size = row_size * height;
+    let offset = 14 + header_size;
+
+    // File header
+    writer.bytes(b"BM");
+    writer.u32_le(offset + image_size);
+    writer.u32_le(0);
+    writer.u32_le(offset);
+
+    // Info header
+    writer.u32_le(header_size);
+    writer.u32_le(glyph.width);
+    writer.u32_le(height);
+    writer.u16_le(1);
+    writer.u16_le(bit_count);
+    writer.u32_le(if transparent { 3 } else { 0 }); // BI_BITFIELDS or BI_RGB
+    writer.u32_le(image_size);
+    writer.u32_le(2835); // 72 DPI
+    writer.u32_le(2835);
+    writer.u32_le(0);
+    writer.u32_le(0);
+
+    if transparent {
+        writer.u32_le(0x00ff0000);
+        writer.u32_le(0x0000ff00);
+        writer.u32_le(0x000000ff);
+        writer.u32_le(0xff000000);
+        writer.bytes(b"BGRs"); // LCS_sRGB
+        writer.bytes(&[0; 48]); // endpoints and gamma, unused for sRGB
+    }
+
+    for y in (0..height).rev() {
+        for x in 0..glyph.width {
+            let pixel = pixels[(y * glyph.width + x) as usize];
+            let [_, r, g, b] = pixel.to_be_bytes();
+
+            if transparent {
+                let a = if pixel == TRANSPARENT { 0 } else { u8::MAX };
+
+                writer.bytes(&[b, g, r, a]);
+            } else {
+                writer.bytes(&[b, g, r]);
+            }
+        }
+
+        for _ in glyph.width * bit_count as u32 / 8..row_size {
+            writer.bytes(&[0]);
+        }
+    }
+}
+
+pub fn glyph_render(env: &Env, glyph: &Glyph, transparent: bool, format: RenderFormat) -> Bytes {
+    let mut sink = BytesSink::new(env);
+
+    match format {
+        RenderFormat::Svg => svg_write(glyph, transparent, &mut sink),
+        RenderFormat::Bmp => bmp_write(glyph, transparent, &mut sink),
+    }
+
+    sink.finish()
+}
+
+// `data:image/svg+xml;base64,...` for when there's no base uri to point at
+pub fn glyph_data_uri(env: &Env, glyph: &Glyph, transparent: bool) -> String {
+    let mut prefix = BytesSink::new(env);
+
+    prefix.bytes(SVG_DATA_URI);
+
+    let mut sink = Base64Sink::new(prefix);
+
+    svg_write(glyph, transparent, &mut sink);
+
+    let uri = sink.finish();
+
+    // `String`s can only be made out of linear memory so this is the one copy sized to the whole uri
+    with_buffer::<_, _, MAX_DATA_URI_SIZE>(uri.len() as usize, 0u8, |buf| {
+        uri.copy_into_slice(buf);
+
+        String::from_bytes(env, buf)
+    })
+}
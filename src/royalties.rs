@@ -0,0 +1,129 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{token, Address, BytesN, Env, Map};
+
+use crate::{
+    events::RoyaltiesPaidEvent,
+    storage::{
+        instance::{read_miner_royalty_rate, read_minter_royalty_rate},
+        persistent::{read_glyph, read_glyph_minter},
+    },
+    types::{Error, RoyaltyBreakdown},
+};
+
+// Most transfers a single sale can make, `glyph_store` won't mint a glyph with more miners than fit
+pub const MAX_SALE_PAYMENTS: u32 = 15;
+
+/* NOTE
+Every sale of a glyph for an asset settles through `royalties_pay`, whether the buyer pays directly or out of escrow
+The minter gets `MinterRoyaltyRate`% of the price and the miners split `MinerRoyaltyRate`% by how many of the glyph's pixels each mined
+Every cut rounds down so royalties can never add up to more than the price, whatever rounding leaves over goes to the seller
+Payees are summed before anything moves so an address that's both minter and miner (or also the seller) gets a single transfer
+A payee who is also the buyer (`payer`) is dropped, direct or out of escrow, and their cut stays with the seller
+`MAX_SALE_PAYMENTS` is checked against what's left after all that, so shared and zero cuts don't count against it
+*/
+
+// payee: amount, summed and without zero amounts
+pub fn royalties_compute(env: &Env, hash: &BytesN<32>, price: i128) -> Result<Map<Address, i128>, Error> {
+    let glyph = read_glyph(env, hash.clone())?;
+    let minter = read_glyph_minter(env, hash.clone()).ok_or(Error::NotFound)?;
+
+    let mut payouts: Map<Address, i128> = Map::new(env);
+
+    let minter_amount = read_minter_royalty_rate(env)
+        .fixed_mul_floor(price, 100)
+        .ok_or(Error::RoyaltyOverflow)?;
+
+    payout_add(&mut payouts, &minter, minter_amount);
+
+    let miners_amount = read_miner_royalty_rate(env)
+        .fixed_mul_floor(price, 100)
+        .ok_or(Error::RoyaltyOverflow)?;
+
+    for (miner, colors_indexes) in glyph.colors.iter() {
+        let mut pixel_count: u32 = 0;
+
+        for (_, indexes) in colors_indexes.iter() {
+            pixel_count += indexes.len();
+        }
+
+        let miner_amount = miners_amount
+            .fixed_mul_floor(pixel_count as i128, glyph.length as i128)
+            .ok_or(Error::RoyaltyOverflow)?;
+
+        payout_add(&mut payouts, &miner, miner_amount);
+    }
+
+    Ok(payouts)
+}
+
+// Moves `price` of `asset` out of `from`, royalties first then the rest to `seller`
+pub fn royalties_pay(
+    env: &Env,
+    hash: &BytesN<32>,
+    asset: &Address,
+    from: &Address,
+    payer: &Address,
+    seller: &Address,
+    price: i128,
+) -> Result<RoyaltyBreakdown, Error> {
+    let mut payouts = royalties_compute(env, hash, price)?;
+
+    payouts.remove(payer.clone());
+
+    // One transfer per payee left plus the seller's unless they're already a payee
+    let payment_count = payouts.len() + if payouts.contains_key(seller.clone()) { 0 } else { 1 };
+
+    if payment_count > MAX_SALE_PAYMENTS {
+        return Err(Error::PaymentCapReached);
+    }
+
+    let mut proceeds = price;
+
+    for (_, amount) in payouts.iter() {
+        proceeds -= amount;
+    }
+
+    // Can only happen if the rates were somehow set past 100%
+    if proceeds < 0 {
+        return Err(Error::RoyaltyOverflow);
+    }
+
+    let token = token::Client::new(env, asset);
+
+    for (payee, amount) in payouts.iter() {
+        if payee != *seller {
+            token.transfer(from, &payee, &amount);
+        }
+    }
+
+    let seller_amount = proceeds + payouts.get(seller.clone()).unwrap_or(0);
+
+    if seller_amount > 0 {
+        token.transfer(from, seller, &seller_amount);
+    }
+
+    let breakdown = RoyaltyBreakdown {
+        price,
+        payouts,
+        proceeds,
+    };
+
+    crate::events::royalties_paid(
+        env,
+        RoyaltiesPaidEvent {
+            hash: hash.clone(),
+            asset: asset.clone(),
+            payer: payer.clone(),
+            seller: seller.clone(),
+            breakdown: breakdown.clone(),
+        },
+    );
+
+    Ok(breakdown)
+}
+
+fn payout_add(payouts: &mut Map<Address, i128>, payee: &Address, amount: i128) {
+    if amount > 0 {
+        payouts.set(payee.clone(), payouts.get(payee.clone()).unwrap_or(0) + amount);
+    }
+}
@@ -5,26 +5,205 @@ use crate::types::{StorageKey, Error};
 pub mod persistent {
     use soroban_sdk::{BytesN, Map, Vec};
 
-    use crate::types::Glyph;
+    use crate::types::{Allowance, Approval, Glyph, GlyphMetadata, MetadataPolicy, Offer, ProvenanceEntry};
 
     use super::*;
 
     pub fn write_color(env: &Env, miner: Address, to: Address, color: u32, amount: u32) {
         let miner_owner_color = StorageKey::Color(miner.clone(), to.clone(), color);
         
+        // Don't pay rent on empty balances
+        if amount == 0 {
+            env.storage().persistent().remove(&miner_owner_color);
+            return;
+        }
+
         env
             .storage()
             .persistent()
             .set::<StorageKey, u32>(&miner_owner_color, &amount);
     }
 
-    pub fn write_colors(env: &Env, minter: Address, colors: &Map<Address, Map<u32, Vec<u32>>>) {
-        let glyph_colors_key = StorageKey::Colors(minter.clone());
+    pub fn write_color_supply(env: &Env, color: u32, supply: u64) {
+        env.storage()
+            .persistent()
+            .set::<StorageKey, u64>(&StorageKey::ColorSupply(color), &supply);
+    }
+
+    pub fn read_color_supply(env: &Env, color: u32) -> u64 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u64>(&StorageKey::ColorSupply(color))
+            .unwrap_or(0)
+    }
+
+    pub fn write_color_burned(env: &Env, color: u32, burned: u64) {
+        env.storage()
+            .persistent()
+            .set::<StorageKey, u64>(&StorageKey::ColorBurned(color), &burned);
+    }
+
+    pub fn read_color_burned(env: &Env, color: u32) -> u64 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u64>(&StorageKey::ColorBurned(color))
+            .unwrap_or(0)
+    }
+
+    // (paid, units) for the units of `color` mined by `miner` that haven't been burned
+    pub fn read_color_cost(env: &Env, miner: Address, color: u32) -> (i128, u64) {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, (i128, u64)>(&StorageKey::ColorCost(miner, color))
+            .unwrap_or((0, 0))
+    }
+
+    pub fn write_color_cost(env: &Env, miner: Address, color: u32, cost: (i128, u64)) {
+        let color_cost_key = StorageKey::ColorCost(miner, color);
+
+        if cost.1 == 0 {
+            env.storage().persistent().remove(&color_cost_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, (i128, u64)>(&color_cost_key, &cost);
+        }
+    }
+
+    pub fn read_color_inventory_length(env: &Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::ColorInventoryLength(owner))
+            .unwrap_or(0)
+    }
+
+    pub fn write_color_inventory_length(env: &Env, owner: Address, length: u32) {
+        let color_inventory_length_key = StorageKey::ColorInventoryLength(owner);
+
+        if length == 0 {
+            env.storage().persistent().remove(&color_inventory_length_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, u32>(&color_inventory_length_key, &length);
+        }
+    }
+
+    // (miner, color)
+    pub fn read_color_inventory(env: &Env, owner: Address, position: u32) -> Option<(Address, u32)> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, (Address, u32)>(&StorageKey::ColorInventory(owner, position))
+    }
+
+    pub fn write_color_inventory(env: &Env, owner: Address, position: u32, miner: Address, color: u32) {
+        env.storage()
+            .persistent()
+            .set::<StorageKey, (Address, u32)>(&StorageKey::ColorInventory(owner.clone(), position), &(miner.clone(), color));
+        env.storage()
+            .persistent()
+            .set::<StorageKey, u32>(&StorageKey::ColorInventoryPosition(owner, miner, color), &position);
+    }
+
+    pub fn read_color_inventory_position(env: &Env, owner: Address, miner: Address, color: u32) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::ColorInventoryPosition(owner, miner, color))
+    }
+
+    pub fn remove_color_inventory(env: &Env, owner: Address, position: u32) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::ColorInventory(owner, position));
+    }
+
+    pub fn remove_color_inventory_position(env: &Env, owner: Address, miner: Address, color: u32) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::ColorInventoryPosition(owner, miner, color));
+    }
+
+    pub fn read_color_total(env: &Env, owner: Address, color: u32) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::ColorTotal(owner, color))
+            .unwrap_or(0)
+    }
+
+    pub fn write_color_total(env: &Env, owner: Address, color: u32, total: u32) {
+        let color_total_key = StorageKey::ColorTotal(owner, color);
+
+        if total == 0 {
+            env.storage().persistent().remove(&color_total_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, u32>(&color_total_key, &total);
+        }
+    }
+
+    pub fn read_color_allowance(env: &Env, owner: Address, spender: Address, miner: Address, color: u32) -> Option<Allowance> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, Allowance>(&StorageKey::ColorAllowance(owner, spender, miner, color))
+    }
+
+    pub fn write_color_allowance(env: &Env, owner: Address, spender: Address, miner: Address, color: u32, allowance: &Allowance) {
+        let color_allowance_key = StorageKey::ColorAllowance(owner, spender, miner, color);
+
+        if allowance.amount == 0 {
+            env.storage().persistent().remove(&color_allowance_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, Allowance>(&color_allowance_key, allowance);
+        }
+    }
+
+    // Also adds the draft to the owner's `Drafts` index
+    pub fn write_draft(env: &Env, owner: Address, draft: u32, colors: &Map<Address, Map<u32, Vec<u32>>>) {
+        let mut drafts = read_drafts(env, owner.clone());
+
+        if let Err(index) = drafts.binary_search(draft) {
+            drafts.insert(index, draft);
+            write_drafts(env, owner.clone(), &drafts);
+        }
 
         env
             .storage()
             .persistent()
-            .set::<StorageKey, Map<Address, Map<u32, Vec<u32>>>>(&glyph_colors_key, colors);
+            .set::<StorageKey, Map<Address, Map<u32, Vec<u32>>>>(&StorageKey::Draft(owner, draft), colors);
+    }
+
+    pub fn read_legacy_colors(env: &Env, owner: Address) -> Option<Map<Address, Map<u32, Vec<u32>>>> {
+        env
+            .storage()
+            .persistent()
+            .get::<StorageKey, Map<Address, Map<u32, Vec<u32>>>>(&StorageKey::Colors(owner))
+    }
+
+    pub fn remove_legacy_colors(env: &Env, owner: Address) {
+        env.storage().persistent().remove(&StorageKey::Colors(owner));
+    }
+
+    // Sorted draft ids
+    pub fn read_drafts(env: &Env, owner: Address) -> Vec<u32> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, Vec<u32>>(&StorageKey::Drafts(owner))
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn write_drafts(env: &Env, owner: Address, drafts: &Vec<u32>) {
+        let drafts_key = StorageKey::Drafts(owner);
+
+        if drafts.is_empty() {
+            env.storage().persistent().remove(&drafts_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, Vec<u32>>(&drafts_key, drafts);
+        }
     }
 
     pub fn read_color(env: &Env, miner: Address, to: Address, color: u32) -> u32 {
@@ -37,21 +216,19 @@ pub mod persistent {
             .unwrap_or(0)
     }
 
-    pub fn read_colors_or_map(env: &Env, minter: Address) -> Map<Address, Map<u32, Vec<u32>>> {
-        read_colors(env, minter).unwrap_or(Map::new(&env))
+    pub fn read_draft_or_map(env: &Env, owner: Address, draft: u32) -> Map<Address, Map<u32, Vec<u32>>> {
+        read_draft(env, owner, draft).unwrap_or(Map::new(env))
     }
 
-    pub fn read_colors_or_error(env: &Env, minter: Address) -> Map<Address, Map<u32, Vec<u32>>> {
-        read_colors(env, minter).unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
+    pub fn read_draft_or_error(env: &Env, owner: Address, draft: u32) -> Map<Address, Map<u32, Vec<u32>>> {
+        read_draft(env, owner, draft).unwrap_or_else(|| panic_with_error!(env, Error::NotFound))
     }
 
-    fn read_colors(env: &Env, minter: Address) -> Option<Map<Address, Map<u32, Vec<u32>>>> {
-        let glyph_colors_key = StorageKey::Colors(minter.clone());
-
+    fn read_draft(env: &Env, owner: Address, draft: u32) -> Option<Map<Address, Map<u32, Vec<u32>>>> {
         env
             .storage()
             .persistent()
-            .get::<StorageKey, Map<Address, Map<u32, Vec<u32>>>>(&glyph_colors_key)
+            .get::<StorageKey, Map<Address, Map<u32, Vec<u32>>>>(&StorageKey::Draft(owner, draft))
     }
 
     pub fn read_glyph(env: &Env, hash: BytesN<32>) -> Result<Glyph, Error> {
@@ -64,32 +241,256 @@ pub mod persistent {
             .ok_or(Error::NotFound)
     }
 
+    pub fn read_glyph_owner(env: &Env, hash: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, Address>(&StorageKey::GlyphOwner(hash))
+    }
+
+    pub fn write_glyph_owner(env: &Env, hash: BytesN<32>, owner: &Address) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphOwner(hash), owner);
+    }
+
+    pub fn read_owner_glyph_count(env: &Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::OwnerGlyphCount(owner))
+            .unwrap_or(0)
+    }
+
+    pub fn write_owner_glyph_count(env: &Env, owner: Address, count: u32) {
+        let owner_glyph_count_key = StorageKey::OwnerGlyphCount(owner);
+
+        if count == 0 {
+            env.storage().persistent().remove(&owner_glyph_count_key);
+        } else {
+            env.storage()
+                .persistent()
+                .set::<StorageKey, u32>(&owner_glyph_count_key, &count);
+        }
+    }
+
+    pub fn read_owner_glyph(env: &Env, owner: Address, position: u32) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, BytesN<32>>(&StorageKey::OwnerGlyph(owner, position))
+    }
+
+    pub fn write_owner_glyph(env: &Env, owner: Address, position: u32, hash: &BytesN<32>) {
+        env.storage()
+            .persistent()
+            .set::<StorageKey, BytesN<32>>(&StorageKey::OwnerGlyph(owner, position), hash);
+        env.storage()
+            .persistent()
+            .set::<StorageKey, u32>(&StorageKey::OwnerGlyphPosition(hash.clone()), &position);
+    }
+
+    pub fn read_owner_glyph_position(env: &Env, hash: BytesN<32>) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::OwnerGlyphPosition(hash))
+    }
+
+    pub fn remove_owner_glyph(env: &Env, owner: Address, position: u32) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::OwnerGlyph(owner, position));
+    }
+
+    pub fn remove_owner_glyph_position(env: &Env, hash: BytesN<32>) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::OwnerGlyphPosition(hash));
+    }
+
+    pub fn read_glyph_approval(env: &Env, hash: BytesN<32>) -> Option<Approval> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, Approval>(&StorageKey::GlyphApproval(hash))
+    }
+
+    pub fn write_glyph_approval(env: &Env, hash: BytesN<32>, approval: &Approval) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphApproval(hash), approval);
+    }
+
+    pub fn remove_glyph_approval(env: &Env, hash: BytesN<32>) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::GlyphApproval(hash));
+    }
+
+    pub fn read_glyph_operator(env: &Env, owner: Address, operator: Address) -> Option<u32> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::GlyphOperator(owner, operator))
+    }
+
+    pub fn write_glyph_operator(env: &Env, owner: Address, operator: Address, live_until_ledger: u32) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphOperator(owner, operator), &live_until_ledger);
+    }
+
+    pub fn remove_glyph_operator(env: &Env, owner: Address, operator: Address) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::GlyphOperator(owner, operator));
+    }
+
+    pub fn read_glyph_minter(env: &Env, hash: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, Address>(&StorageKey::GlyphMinter(hash))
+    }
+
+    pub fn write_glyph_minter(env: &Env, hash: BytesN<32>, minter: &Address) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphMinter(hash), minter);
+    }
+
+    pub fn read_glyph_history_length(env: &Env, hash: BytesN<32>) -> u32 {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, u32>(&StorageKey::GlyphHistoryLength(hash))
+            .unwrap_or(0)
+    }
+
+    pub fn read_glyph_history_entry(env: &Env, hash: BytesN<32>, index: u32) -> Option<ProvenanceEntry> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, ProvenanceEntry>(&StorageKey::GlyphHistory(hash, index))
+    }
+
+    // Appends, every entry is its own ledger entry so a busy glyph's history never outgrows one
+    pub fn write_glyph_history_entry(env: &Env, hash: BytesN<32>, entry: &ProvenanceEntry) {
+        let length = read_glyph_history_length(env, hash.clone());
+
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphHistory(hash.clone(), length), entry);
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphHistoryLength(hash), &(length + 1));
+    }
+
+    pub fn read_glyph_metadata(env: &Env, hash: BytesN<32>) -> Option<GlyphMetadata> {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, GlyphMetadata>(&StorageKey::GlyphMetadata(hash))
+    }
+
+    pub fn write_glyph_metadata(env: &Env, hash: BytesN<32>, metadata: &GlyphMetadata) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphMetadata(hash), metadata);
+    }
+
+    pub fn remove_glyph_metadata(env: &Env, hash: BytesN<32>) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::GlyphMetadata(hash));
+    }
+
+    pub fn read_glyph_metadata_policy(env: &Env, hash: BytesN<32>) -> MetadataPolicy {
+        env.storage()
+            .persistent()
+            .get::<StorageKey, MetadataPolicy>(&StorageKey::GlyphMetadataPolicy(hash))
+            .unwrap_or(MetadataPolicy::Owner)
+    }
+
+    pub fn write_glyph_metadata_policy(env: &Env, hash: BytesN<32>, policy: &MetadataPolicy) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphMetadataPolicy(hash), policy);
+    }
+
+    pub fn remove_glyph_metadata_policy(env: &Env, hash: BytesN<32>) {
+        env.storage()
+            .persistent()
+            .remove(&StorageKey::GlyphMetadataPolicy(hash));
+    }
+
+    pub fn read_glyph_transparent(env: &Env, hash: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&StorageKey::GlyphTransparent(hash))
+    }
+
+    pub fn write_glyph_transparent(env: &Env, hash: BytesN<32>) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphTransparent(hash), &true);
+    }
+
     pub fn remove_glyph_owner(env: &Env, hash: BytesN<32>) {
         env.storage()
             .persistent()
             .remove(&StorageKey::GlyphOwner(hash));
     }
 
+    // Every sell offer on the glyph along with who posted them
     pub fn remove_glyph_offer(env: &Env, hash: BytesN<32>) {
+        let glyph_offer_key = StorageKey::GlyphOffer(hash.clone());
+
+        if let Some(offers) = env.storage().persistent().get::<StorageKey, Vec<Offer>>(&glyph_offer_key) {
+            for offer in offers.iter() {
+                remove_glyph_offer_spender(env, hash.clone(), offer);
+            }
+
+            env.storage().persistent().remove(&glyph_offer_key);
+        }
+    }
+
+    pub fn read_glyph_offer_spender(env: &Env, hash: BytesN<32>, offer: Offer) -> Option<Address> {
         env.storage()
             .persistent()
-            .remove(&StorageKey::GlyphOffer(hash));
+            .get::<StorageKey, Address>(&StorageKey::GlyphOfferSpender(hash, offer))
     }
 
-    pub fn remove_colors(env: &Env, owner: Address) {
-        let colors_key = StorageKey::Colors(owner);
-        env.storage().persistent().remove(&colors_key);
+    pub fn write_glyph_offer_spender(env: &Env, hash: BytesN<32>, offer: Offer, spender: &Address) {
+        env.storage()
+            .persistent()
+            .set(&StorageKey::GlyphOfferSpender(hash, offer), spender);
     }
 
-    pub fn has_colors(env: &Env, owner: Address) -> bool {
-        env
-            .storage()
+    pub fn remove_glyph_offer_spender(env: &Env, hash: BytesN<32>, offer: Offer) {
+        env.storage()
             .persistent()
-            .has(&StorageKey::Colors(owner))
+            .remove(&StorageKey::GlyphOfferSpender(hash, offer));
+    }
+
+    // Also drops the draft from the owner's `Drafts` index
+    pub fn remove_draft(env: &Env, owner: Address, draft: u32) {
+        let draft_key = StorageKey::Draft(owner.clone(), draft);
+
+        if !env.storage().persistent().has(&draft_key) {
+            return;
+        }
+
+        env.storage().persistent().remove(&draft_key);
+
+        let mut drafts = read_drafts(env, owner.clone());
+
+        if let Ok(index) = drafts.binary_search(draft) {
+            drafts.remove(index);
+            write_drafts(env, owner, &drafts);
+        }
     }
 }
 
 pub mod instance {
+    use soroban_sdk::{Map, String, Vec};
+
+    use crate::{
+        contract::{DEFAULT_MAX_HEIGHT, DEFAULT_MAX_WIDTH},
+        types::{Canvas, MinePrice, PriceCurve, RemintPolicy},
+    };
+
     use super::*;
     
     pub fn write_owner_address(env: &Env, owner: &Address) {
@@ -134,6 +535,48 @@ pub mod instance {
             .set(&StorageKey::MinerRoyaltyRate, miner_royalty_rate);
     }
 
+    pub fn write_base_uri(env: &Env, base_uri: &String) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::BaseUri, base_uri);
+    }
+
+    pub fn write_mine_price(env: &Env, mine_price: &MinePrice) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::MinePrice, mine_price);
+    }
+
+    pub fn write_price_curve(env: &Env, price_curve: &PriceCurve) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::PriceCurve, price_curve);
+    }
+
+    pub fn write_treasury_balance(env: &Env, balance: &i128) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::TreasuryBalance, balance);
+    }
+
+    pub fn write_burn_refund_rate(env: &Env, rate: &i128) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::BurnRefundRate, rate);
+    }
+
+    pub fn write_remint_policy(env: &Env, policy: &RemintPolicy) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::RemintPolicy, policy);
+    }
+
+    pub fn write_canvas(env: &Env, canvas: &Canvas) {
+        env.storage()
+            .instance()
+            .set(&StorageKey::Canvas, canvas);
+    }
+
     pub fn read_owner_address(env: &Env) -> Address {
         env.storage()
                 .instance()
@@ -181,5 +624,62 @@ pub mod instance {
             .get(&StorageKey::MinerRoyaltyRate)
             .unwrap_or_else(|| panic_with_error!(env, Error::NotInitialized))
     }
+
+    // Defaults to the original flat stroop per pixel
+    pub fn read_mine_price(env: &Env) -> MinePrice {
+        env.storage()
+            .instance()
+            .get(&StorageKey::MinePrice)
+            .unwrap_or(MinePrice {
+                base_price: 1,
+                color_multipliers: Map::new(env),
+                volume_discounts: Vec::new(env),
+            })
+    }
+
+    pub fn read_price_curve(env: &Env) -> PriceCurve {
+        env.storage()
+            .instance()
+            .get(&StorageKey::PriceCurve)
+            .unwrap_or(PriceCurve::Flat)
+    }
+
+    pub fn read_treasury_balance(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::TreasuryBalance)
+            .unwrap_or(0)
+    }
+
+    pub fn read_burn_refund_rate(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&StorageKey::BurnRefundRate)
+            .unwrap_or(0)
+    }
+
+    pub fn read_remint_policy(env: &Env) -> RemintPolicy {
+        env.storage()
+            .instance()
+            .get(&StorageKey::RemintPolicy)
+            .unwrap_or(RemintPolicy::OriginalMinter)
+    }
+
+    pub fn read_canvas(env: &Env) -> Canvas {
+        env.storage()
+            .instance()
+            .get(&StorageKey::Canvas)
+            .unwrap_or(Canvas {
+                max_width: DEFAULT_MAX_WIDTH,
+                max_height: DEFAULT_MAX_HEIGHT,
+            })
+    }
+
+    pub fn read_base_uri(env: &Env) -> String {
+        env.storage()
+            .instance()
+            .get(&StorageKey::BaseUri)
+            .unwrap_or(String::from_str(env, ""))
+    }
     
 }
\ No newline at end of file
@@ -3,8 +3,16 @@
 // use std::println;
 // extern crate std;
 
-use crate::contract::{ColorGlyph, ColorGlyphClient};
-use soroban_sdk::{testutils::Address as _, token, vec, Address, Env, Map};
+use crate::{
+    contract::{ColorGlyph, ColorGlyphClient},
+    storage::persistent::{write_color, write_color_supply},
+    types::{Error, HashType, PriceCurve},
+};
+use soroban_sdk::{
+    map,
+    testutils::{Address as _, Ledger},
+    token, vec, Address, Env, Map,
+};
 
 #[test]
 fn test() {
@@ -42,13 +50,13 @@ fn test() {
         colors.set(i, 1);
     }
 
-    client.colors_mine(&u1_address, &colors, &None, &None);
+    client.colors_mine(&u1_address, &colors, &None, &None, &None);
 
     let color = client.color_balance(&u1_address.clone(), &0, &None);
 
     assert_eq!(color, 1);
 
-    client.colors_mine(&u2_address, &colors, &None, &Some(u1_address.clone()));
+    client.colors_mine(&u2_address, &colors, &None, &Some(u1_address.clone()), &None);
 
     let color1 = client.color_balance(&u1_address.clone(), &0, &None);
     let color2 = client.color_balance(&u1_address.clone(), &0, &Option::Some(u2_address.clone()));
@@ -78,3 +86,501 @@ fn test() {
 
     // println!("{:?}", env.budget().print());
 }
+
+#[test]
+fn test_inventory() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 5), (1, 5)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (0, 3)], &None, &Some(u1_address.clone()), &None);
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![
+            &env,
+            (u1_address.clone(), 0, 5),
+            (u1_address.clone(), 1, 5),
+            (u2_address.clone(), 0, 3)
+        ]
+    );
+    assert_eq!(
+        client.colors_of(&u1_address, &1, &1),
+        vec![&env, (u1_address.clone(), 1, 5)]
+    );
+    assert_eq!(client.color_total(&u1_address, &0), 8);
+
+    // Emptied balances drop out of the inventory
+    client.colors_transfer(
+        &u1_address,
+        &u2_address,
+        &vec![&env, (u1_address.clone(), 1, 5), (u2_address.clone(), 0, 1)],
+    );
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![&env, (u1_address.clone(), 0, 5), (u2_address.clone(), 0, 2)]
+    );
+    assert_eq!(
+        client.colors_of(&u2_address, &0, &10),
+        vec![&env, (u1_address.clone(), 1, 5), (u2_address.clone(), 0, 1)]
+    );
+
+    // Self transfers are a no-op
+    client.colors_transfer(&u1_address, &u1_address, &vec![&env, (u1_address.clone(), 0, 5)]);
+
+    assert_eq!(client.color_total(&u1_address, &0), 7);
+
+    // Minting spends colors out of the inventory and scraping returns them
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (u2_address.clone(), map![&env, (0, vec![&env, 0, 1])])
+            ],
+            &Some(2),
+            &None,
+        )
+        .unwrap();
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![&env, (u1_address.clone(), 0, 5)]
+    );
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash), &None, &None);
+
+    assert_eq!(
+        client.colors_of(&u1_address, &0, &10),
+        vec![&env, (u1_address.clone(), 0, 5), (u2_address.clone(), 0, 2)]
+    );
+    assert_eq!(client.color_total(&u1_address, &0), 7);
+
+    // Balances from before the inventory existed get picked up when they're next touched or by `color_index`
+    let u3_address = Address::generate(&env);
+
+    env.as_contract(&contract_address, || {
+        write_color(&env, u1_address.clone(), u3_address.clone(), 0, 4);
+        write_color(&env, u2_address.clone(), u3_address.clone(), 0, 6);
+    });
+
+    assert_eq!(client.colors_of(&u3_address, &0, &10), vec![&env]);
+    assert_eq!(client.color_total(&u3_address, &0), 0);
+
+    client.colors_transfer(&u1_address, &u3_address, &vec![&env, (u1_address.clone(), 0, 1)]);
+    client.color_index(&u3_address, &u2_address, &0);
+    client.color_index(&u3_address, &u2_address, &0);
+    client.color_index(&u3_address, &u2_address, &1); // nothing there
+
+    assert_eq!(
+        client.colors_of(&u3_address, &0, &10),
+        vec![&env, (u1_address.clone(), 0, 5), (u2_address.clone(), 0, 6)]
+    );
+    assert_eq!(client.color_total(&u3_address, &0), 11);
+
+    client.colors_transfer(&u3_address, &u1_address, &vec![&env, (u1_address.clone(), 0, 5)]);
+
+    assert_eq!(client.colors_of(&u3_address, &0, &10), vec![&env, (u2_address.clone(), 0, 6)]);
+    assert_eq!(client.color_total(&u3_address, &0), 6);
+}
+
+#[test]
+fn test_allowance() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let minter_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 5)], &None, &None, &None);
+
+    assert_eq!(
+        client.try_colors_transfer_from(
+            &minter_address,
+            &u1_address,
+            &minter_address,
+            &vec![&env, (u1_address.clone(), 0, 1)]
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    client.colors_approve(&u1_address, &minter_address, &u1_address, &0, &3, &200);
+
+    assert_eq!(client.colors_allowance(&u1_address, &minter_address, &u1_address, &0), 3);
+
+    client.colors_transfer_from(
+        &minter_address,
+        &u1_address,
+        &minter_address,
+        &vec![&env, (u1_address.clone(), 0, 2)],
+    );
+
+    assert_eq!(client.colors_allowance(&u1_address, &minter_address, &u1_address, &0), 1);
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 3);
+    assert_eq!(client.color_balance(&minter_address, &0, &Some(u1_address.clone())), 2);
+
+    // The spender can build a glyph from the colors it pulled
+    let hash = client
+        .glyph_mint(
+            &minter_address,
+            &Some(u1_address.clone()),
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])],
+            &Some(2),
+            &None,
+        )
+        .unwrap();
+
+    assert_eq!(client.owner_of(&hash), u1_address);
+
+    // Allowances can't be overspent and don't outlive their expiration ledger
+    assert_eq!(
+        client.try_colors_transfer_from(
+            &minter_address,
+            &u1_address,
+            &minter_address,
+            &vec![&env, (u1_address.clone(), 0, 2)]
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    env.ledger().with_mut(|li| li.sequence_number = 201);
+
+    assert_eq!(client.colors_allowance(&u1_address, &minter_address, &u1_address, &0), 0);
+    assert_eq!(
+        client.try_colors_transfer_from(
+            &minter_address,
+            &u1_address,
+            &minter_address,
+            &vec![&env, (u1_address.clone(), 0, 1)]
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+}
+
+#[test]
+fn test_mine_price() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &100_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    assert_eq!(client.colors_quote(&map![&env, (0, 5), (1, 5)]), 10);
+
+    client.update_mine_price(
+        &Some(10),
+        &Some(map![&env, (0, 250), (1, 50)]),
+        &Some(vec![&env, (100, 10), (1_000, 25)]),
+    );
+
+    // 5 * 10 * 2.5 + 5 * 10 * 0.5 + 2 * 10
+    assert_eq!(client.colors_quote(&map![&env, (0, 5), (1, 5), (2, 2)]), 170);
+
+    // 100 * 10 * 0.9
+    assert_eq!(client.colors_quote(&map![&env, (2, 100)]), 900);
+    assert_eq!(client.colors_quote(&map![&env, (2, 1_000)]), 7_500);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 5), (1, 5), (2, 2)], &None, &None, &None);
+
+    assert_eq!(token_client.balance(&fee_address), 170);
+    assert_eq!(token_client.balance(&u1_address), 100_000 - 170);
+
+    // Unsorted discount tiers and discounts over 100% are rejected
+    assert_eq!(
+        client.try_update_mine_price(&None, &None, &Some(vec![&env, (1_000, 10), (100, 25)])),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidPrice)))
+    );
+    assert_eq!(
+        client.try_update_mine_price(&None, &None, &Some(vec![&env, (100, 101)])),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidPrice)))
+    );
+    assert_eq!(
+        client.try_update_mine_price(&Some(-1), &None, &None),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidPrice)))
+    );
+}
+
+#[test]
+fn test_price_curve() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &100_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.update_mine_price(&Some(10), &None, &None);
+    client.update_price_curve(&PriceCurve::Linear(1_000_000)); // +10% per mined unit
+
+    // 10 * (10 + 0.1 * (0 + 1 + ... + 9))
+    assert_eq!(client.colors_quote(&map![&env, (0, 10)]), 145);
+
+    assert_eq!(
+        client.try_colors_mine(&u1_address, &map![&env, (0, 10)], &None, &None, &Some(144)),
+        Err(Ok(soroban_sdk::Error::from(Error::MaxPriceExceeded)))
+    );
+
+    client.colors_mine(&u1_address, &map![&env, (0, 10)], &None, &None, &Some(145));
+
+    assert_eq!(client.color_supply(&0), 10);
+    assert_eq!(client.color_supply(&1), 0);
+    assert_eq!(token_client.balance(&fee_address), 145);
+
+    // Popular colors get more expensive
+    assert_eq!(client.colors_quote(&map![&env, (0, 10)]), 245);
+    assert_eq!(client.colors_quote(&map![&env, (1, 10)]), 145);
+
+    // Doubling with every mined unit
+    client.update_mine_price(&Some(1), &None, &None);
+    client.update_price_curve(&PriceCurve::Exponential(10_000_000));
+
+    // 1 + 2 + 4
+    assert_eq!(client.colors_quote(&map![&env, (1, 3)]), 7);
+
+    client.colors_mine(&u1_address, &map![&env, (1, 3)], &None, &None, &None);
+
+    assert_eq!(client.colors_quote(&map![&env, (1, 1)]), 8);
+
+    // Compounding stops at 1000x, 2^10 = 1024 is the first doubling past it
+    // 8 + 16 + ... + 512 + 3 * 1024
+    assert_eq!(client.colors_quote(&map![&env, (1, 10)]), 4_088);
+
+    // Supplies that would overflow an uncapped curve still quote
+    env.as_contract(&contract_address, || {
+        write_color_supply(&env, 2, u32::MAX as u64);
+    });
+
+    assert_eq!(client.colors_quote(&map![&env, (2, 2)]), 2_048);
+
+    assert_eq!(
+        client.try_colors_quote(&map![&env, (1, u32::MAX), (2, 1)]),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidAmount)))
+    );
+
+    assert_eq!(
+        client.try_update_price_curve(&PriceCurve::Linear(-1)),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidRate)))
+    );
+}
+
+#[test]
+fn test_burn() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+    let token_client = token::Client::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.update_mine_price(&Some(10), &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (0, 10), (1, 1)], &None, &None, &None);
+
+    // No refunds until the owner opts in
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 1, 1)]), 0);
+    assert_eq!(client.color_burned(&1), 1);
+    assert_eq!(
+        client.colors_of(&u2_address, &0, &10),
+        vec![&env, (u2_address.clone(), 0, 10)]
+    );
+
+    client.update_burn_refund_rate(&50);
+    client.treasury_deposit(&u1_address, &30);
+
+    assert_eq!(client.treasury_balance(), 30);
+
+    // 4 * 10 * 50%
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 0, 4)]), 20);
+    assert_eq!(client.treasury_balance(), 10);
+
+    // Refunds are capped at whatever is left in the treasury
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 0, 4)]), 10);
+    assert_eq!(client.treasury_balance(), 0);
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 0, 1)]), 0);
+
+    assert_eq!(token_client.balance(&u2_address), 10_000 - 110 + 30);
+    assert_eq!(client.color_balance(&u2_address, &0, &None), 1);
+    assert_eq!(client.color_burned(&0), 9);
+    assert_eq!(client.color_supply(&0), 10);
+
+    assert_eq!(
+        client.try_colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 0, 2)]),
+        Err(Ok(soroban_sdk::Error::from(Error::InsufficientColors)))
+    );
+    assert_eq!(
+        client.try_treasury_withdraw(&u1_address, &1),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidAmount)))
+    );
+    assert_eq!(
+        client.try_update_burn_refund_rate(&101),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidRate)))
+    );
+
+    client.treasury_deposit(&u1_address, &5);
+
+    // The token can't change under a funded treasury
+    let token2_address = env.register_stellar_asset_contract(token_admin.clone());
+
+    assert_eq!(
+        client.try_update(&None, &Some(token2_address.clone()), &None, &None, &None, &None, &None),
+        Err(Ok(soroban_sdk::Error::from(Error::NotEmpty)))
+    );
+
+    client.treasury_withdraw(&u1_address, &5);
+    client.update(&None, &Some(token2_address), &None, &None, &None, &None, &None);
+
+    assert_eq!(token_client.balance(&u1_address), 10_000 - 30);
+}
+
+#[test]
+fn test_burn_refund_cost() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.update_burn_refund_rate(&100);
+    client.treasury_deposit(&u1_address, &5_000);
+
+    client.update_mine_price(&Some(100), &None, &None);
+    client.colors_mine(&u1_address, &map![&env, (0, 10)], &None, &None, &None);
+
+    // Dropping the price doesn't raise the refund over what was paid
+    client.update_mine_price(&Some(10), &None, &Some(vec![&env, (10, 50)]));
+    client.colors_mine(&u2_address, &map![&env, (0, 10)], &None, &None, &None);
+
+    // 10 * 10 * 50%
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 0, 10)]), 50);
+
+    // Burning half of u1's units refunds half of what u1 paid
+    assert_eq!(client.colors_burn(&u1_address, &vec![&env, (u1_address.clone(), 0, 5)]), 500);
+
+    client.colors_transfer(&u1_address, &u2_address, &vec![&env, (u1_address.clone(), 0, 5)]);
+
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u1_address.clone(), 0, 5)]), 500);
+
+    // Units mined before costs were tracked refund nothing
+    env.as_contract(&contract_address, || {
+        write_color(&env, u2_address.clone(), u2_address.clone(), 1, 3);
+    });
+
+    assert_eq!(client.colors_burn(&u2_address, &vec![&env, (u2_address.clone(), 1, 3)]), 0);
+    assert_eq!(client.treasury_balance(), 5_000 - 1_050);
+}
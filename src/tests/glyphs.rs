@@ -5,10 +5,20 @@ extern crate std;
 
 use crate::{
     contract::{ColorGlyph, ColorGlyphClient},
-    types::{Error, GlyphType, HashType, StorageKey},
+    events::{ColorsInEvent, ColorsOutEvent, DraftEraseEvent, GlyphScrapeCompleteEvent},
+    storage::persistent::remove_draft,
+    testutils::{Fixture, BLANK},
+    types::{
+        Canvas, Error, GlyphStatus, GlyphType, HashType, MintOptions, Offer, PixelSpan, Provenance, ProvenanceEntry,
+        RemintPolicy, RenderFormat, ScrapeProgress, StorageKey,
+    },
 };
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{map, testutils::Address as _, token, vec, Address, Env};
+use soroban_sdk::{
+    map,
+    testutils::{Address as _, Events, Ledger},
+    token, vec, Address, Bytes, BytesN, Env, FromVal, IntoVal, Map, Symbol, Val, Vec,
+};
 
 mod colorglyph {
     soroban_sdk::contractimport!(
@@ -71,13 +81,14 @@ fn big_mint() {
         }
     }
 
-    client.colors_mine(&u1_address, &mine_colors, &None, &None);
+    client.colors_mine(&u1_address, &mine_colors, &None, &None, &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u1_address.clone(), mint_colors)],
         &None,
+        &None,
     );
 
     // env.budget().reset_default();
@@ -85,7 +96,7 @@ fn big_mint() {
     env.budget().reset_unlimited();
 
     let id = client
-        .glyph_mint(&u1_address, &None, &map, &Some(width as u32))
+        .glyph_mint(&u1_address, &None, &map, &Some(width as u32), &None)
         .unwrap();
 
     // 40
@@ -128,6 +139,7 @@ fn toolbox_test() {
         &map![&env, (0, 100), (16777215, 100),],
         &None,
         &None,
+        &None,
     );
 
     let id = client.glyph_mint(
@@ -141,6 +153,7 @@ fn toolbox_test() {
             )
         ],
         &None,
+        &None,
     );
 
     println!("{:?}", id);
@@ -174,6 +187,7 @@ fn test_dupe_mint() {
         &map![&env, (0, 100), (16777215, 100),],
         &None,
         &None,
+        &None,
     );
 
     let hash = client.glyph_mint(
@@ -187,6 +201,7 @@ fn test_dupe_mint() {
             )
         ],
         &Some(2),
+        &None,
     );
 
     println!("{:?}\n", hash);
@@ -203,8 +218,9 @@ fn test_dupe_mint() {
                 )
             ],
             &Some(2),
+            &None,
         ),
-        Err(Ok(soroban_sdk::Error::from(Error::NotEmpty)))
+        Err(Ok(soroban_sdk::Error::from(Error::GlyphOwned)))
     );
 }
 
@@ -246,6 +262,7 @@ fn test_to_mint() {
         ],
         &None,
         &None,
+        &None,
     );
 
     client.glyph_mint(
@@ -265,6 +282,7 @@ fn test_to_mint() {
             )
         ],
         &None,
+        &None,
     );
 
     let hash = client
@@ -285,6 +303,7 @@ fn test_to_mint() {
                 )
             ],
             &Some(8),
+            &None,
         )
         .unwrap();
 
@@ -343,6 +362,7 @@ fn test_partial_mint() {
         ],
         &None,
         &None,
+        &None,
     );
     client.colors_mine(
         &u2_address,
@@ -361,6 +381,7 @@ fn test_partial_mint() {
         ],
         &None,
         &Some(u1_address.clone()),
+        &None,
     );
 
     client.glyph_mint(
@@ -381,6 +402,7 @@ fn test_partial_mint() {
             )
         ],
         &None,
+        &None,
     );
 
     client.glyph_mint(
@@ -401,6 +423,7 @@ fn test_partial_mint() {
             )
         ],
         &None,
+        &None,
     );
     client.glyph_mint(
         &u1_address,
@@ -435,6 +458,7 @@ fn test_partial_mint() {
             )
         ],
         &None,
+        &None,
     );
     client.glyph_mint(
         &u1_address,
@@ -469,15 +493,16 @@ fn test_partial_mint() {
             )
         ],
         &None,
+        &None,
     );
 
     println!(
         "{:?}\n",
-        client.glyph_get(&HashType::Colors(u1_address.clone()))
+        client.glyph_get(&HashType::Colors(u1_address.clone(), 0))
     );
 
     let hash = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(14))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(14), &None)
         .unwrap();
 
     println!("{:?}", hash);
@@ -489,7 +514,7 @@ fn test_partial_mint() {
         _ => panic!(),
     }
 
-    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()));
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
 
     assert_eq!(
         client.try_glyph_get(&HashType::Glyph(hash.clone())),
@@ -498,13 +523,18 @@ fn test_partial_mint() {
 
     assert_ne!(
         // not equals (v important as the glyph shouldn't be fully scraped yet)
-        client.try_glyph_get(&HashType::Colors(u1_address.clone())),
+        client.try_glyph_get(&HashType::Colors(u1_address.clone(), 1)),
         Err(Ok(Error::NotFound))
     );
 
     assert_eq!(
-        client.glyph_scrape(&None, &HashType::Colors(u1_address.clone())),
-        ()
+        client.glyph_scrape(&None, &HashType::Colors(u1_address.clone(), 1), &None, &None),
+        ScrapeProgress {
+            draft: None,
+            miners: 0,
+            colors: 0,
+            pixels: 0,
+        }
     );
 
     // assert_eq!(
@@ -513,9 +543,1003 @@ fn test_partial_mint() {
     // );
 
     assert_eq!(
-        client.try_glyph_get(&HashType::Colors(u1_address.clone())),
+        client.try_glyph_get(&HashType::Colors(u1_address.clone(), 1)),
         Err(Ok(Error::NotFound))
     );
 
     // println!("{:?}", env.budget().print());
 }
+
+#[test]
+fn test_glyphs_of() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 100)], &None, &None, &None);
+
+    let mut hashes = vec![&env];
+
+    for i in 1..=3 {
+        let hash = client
+            .glyph_mint(
+                &u1_address,
+                &None,
+                &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+                &Some(i),
+                &None,
+            )
+            .unwrap();
+
+        hashes.push_back(hash);
+    }
+
+    assert_eq!(client.glyph_count(&u1_address), 3);
+    assert_eq!(client.glyphs_of(&u1_address, &0, &2), hashes.slice(0..2));
+    assert_eq!(client.glyphs_of(&u1_address, &2, &2), hashes.slice(2..3));
+    assert_eq!(client.glyphs_of(&u1_address, &5, &2), vec![&env]);
+
+    client.glyph_transfer(&u2_address, &HashType::Glyph(hashes.get(0).unwrap()), &None);
+    client.glyph_scrape(&None, &HashType::Glyph(hashes.get(1).unwrap()), &None, &None);
+
+    assert_eq!(client.glyph_count(&u1_address), 1);
+    assert_eq!(client.glyphs_of(&u1_address, &0, &10), hashes.slice(2..3));
+    assert_eq!(client.glyphs_of(&u2_address, &0, &10), hashes.slice(0..1));
+
+    // A glyph owned from before the index existed isn't listed until it's backfilled
+    let legacy = hashes.get(2).unwrap();
+
+    env.as_contract(&contract_address, || {
+        env.storage().persistent().remove(&StorageKey::OwnerGlyph(u1_address.clone(), 0));
+        env.storage().persistent().remove(&StorageKey::OwnerGlyphPosition(legacy.clone()));
+        env.storage().persistent().remove(&StorageKey::OwnerGlyphCount(u1_address.clone()));
+    });
+
+    assert_eq!(client.glyph_count(&u1_address), 0);
+
+    client.glyph_index(&legacy);
+    client.glyph_index(&legacy);
+
+    assert_eq!(client.glyph_count(&u1_address), 1);
+    assert_eq!(client.glyphs_of(&u1_address, &0, &10), vec![&env, legacy.clone()]);
+    assert_eq!(
+        client.try_glyph_index(&hashes.get(1).unwrap()),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+
+    // Removing from the middle moves the last glyph into the gap
+    client.glyph_transfer(&u2_address, &HashType::Glyph(legacy.clone()), &None);
+
+    assert_eq!(client.glyphs_of(&u2_address, &0, &10), vec![&env, hashes.get(0).unwrap(), legacy.clone()]);
+
+    client.glyph_transfer(&u1_address, &HashType::Glyph(hashes.get(0).unwrap()), &None);
+
+    assert_eq!(client.glyph_count(&u2_address), 1);
+    assert_eq!(client.glyphs_of(&u2_address, &0, &10), vec![&env, legacy]);
+}
+
+#[test]
+fn test_mint_validation() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 2), (1, 100)], &None, &None, &None);
+
+    // Spending more colors than you hold
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 1, 2])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::InsufficientColors)))
+    );
+
+    // Off the canvas
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (1, vec![&env, 1600])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::IndexOutOfRange)))
+    );
+
+    // Duplicate indexes within a single mint
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (u1_address.clone(), map![&env, (0, vec![&env, 0]), (1, vec![&env, 0])])
+            ],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+
+    // Duplicate indexes across progressive mints
+    client.glyph_mint(
+        &u1_address,
+        &None,
+        &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 1])])],
+        &None,
+        &None,
+    );
+
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (1, vec![&env, 2, 1])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+
+    // Widths that don't fit the canvas
+    client.glyph_mint(
+        &u1_address,
+        &None,
+        &map![&env, (u1_address.clone(), map![&env, (1, vec![&env, 80])])],
+        &None,
+        &None,
+    );
+
+    for width in [0, 1, 41] {
+        assert_eq!(
+            client.try_glyph_mint(&u1_address, &None, &map![&env], &Some(width), &None),
+            Err(Ok(soroban_sdk::Error::from(Error::InvalidWidth)))
+        );
+    }
+
+    client.glyph_mint(&u1_address, &None, &map![&env], &Some(3), &None).unwrap();
+
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 0);
+    assert_eq!(client.color_balance(&u1_address, &1, &None), 99);
+}
+
+#[test]
+fn test_mint_spans() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 12), (0xff0000, 1)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (
+                    u1_address.clone(),
+                    map![
+                        &env,
+                        (0, vec![&env, 0, 1, 2, 3, 4, 5, 8, 9, 12, 13, 14, 15]),
+                        (0xff0000, vec![&env, 7])
+                    ]
+                )
+            ],
+            &Some(4),
+            &None,
+        )
+        .unwrap();
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    // The same picture in shorthand lands on the same hash
+    let span_hash = client
+        .glyph_mint_spans(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (
+                    u1_address.clone(),
+                    map![
+                        &env,
+                        (
+                            0,
+                            vec![&env, PixelSpan::Range(0, 5), PixelSpan::Run(8, 2), PixelSpan::Row(3, 0, 3)]
+                        ),
+                        (0xff0000, vec![&env, PixelSpan::Index(7)])
+                    ]
+                )
+            ],
+            &Some(4),
+            &None,
+        )
+        .unwrap();
+
+    assert_eq!(span_hash, hash);
+    assert_eq!(client.color_balance(&u1_address, &0, &None), 0);
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    // Rows need to know the width
+    assert_eq!(
+        client.try_glyph_mint_spans(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, PixelSpan::Row(0, 0, 1)])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::MissingWidth)))
+    );
+
+    assert_eq!(
+        client.try_glyph_mint_spans(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, PixelSpan::Range(5, 2)])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::IndexOutOfRange)))
+    );
+    assert_eq!(
+        client.try_glyph_mint_spans(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, PixelSpan::Row(0, 2, 4)])])],
+            &Some(4),
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::IndexOutOfRange)))
+    );
+
+    // Can't expand past a full canvas
+    assert_eq!(
+        client.try_glyph_mint_spans(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (
+                    u1_address.clone(),
+                    map![&env, (0, vec![&env, PixelSpan::Range(0, 1599), PixelSpan::Range(0, 1599)])]
+                )
+            ],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+}
+
+#[test]
+fn test_canvas() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    client.colors_mine(&u1_address, &map![&env, (0, 10)], &None, &None, &None);
+
+    assert_eq!(client.glyph_canvas(), Canvas { max_width: 40, max_height: 40 });
+
+    assert_eq!(
+        client.try_update_canvas(&0, &40),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidCanvas)))
+    );
+    assert_eq!(
+        client.try_update_canvas(&100, &41),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidCanvas)))
+    );
+
+    client.update_canvas(&300, &2);
+
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 599])])];
+
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 600])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::IndexOutOfRange)))
+    );
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &colors, &Some(301), &None),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidWidth)))
+    );
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &colors, &Some(200), &None),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidWidth)))
+    );
+
+    let hash = client
+        .glyph_mint(&u1_address, &None, &colors, &Some(300), &None)
+        .unwrap();
+
+    // Wider than a byte so the full u32 width and version 1 go into the preimage
+    let mut preimage = [u8::MAX; 600 * 3 + 6];
+
+    preimage[..3].copy_from_slice(&[0, 0, 0]);
+    preimage[599 * 3..600 * 3].copy_from_slice(&[0, 0, 0]);
+    preimage[600 * 3..].copy_from_slice(&[0, 0, 1, 44, 0, 1]);
+
+    assert_eq!(hash, env.crypto().sha256(&Bytes::from_slice(&env, &preimage)));
+
+    match client.glyph_get(&HashType::Glyph(hash.clone())) {
+        GlyphType::Glyph(glyph) => {
+            assert_eq!(glyph.width, 300);
+            assert_eq!(glyph.length, 600);
+        }
+        _ => panic!(),
+    }
+
+    assert_eq!(client.glyph_render(&hash, &RenderFormat::Bmp).len(), 54 + 900 * 2);
+}
+
+#[test]
+fn test_batched_events() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    let mut colors_amounts: Map<u32, u32> = Map::new(&env);
+    let mut colors_indexes: Map<u32, Vec<u32>> = Map::new(&env);
+
+    for color in 0..20 {
+        colors_amounts.set(color, 2);
+        colors_indexes.set(color, vec![&env, color * 2, color * 2 + 1]);
+    }
+
+    client.colors_mine(&u1_address, &colors_amounts, &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), colors_indexes)],
+            &Some(8),
+            &None,
+        )
+        .unwrap();
+
+    let contract_events = |env: &Env, name: &str| {
+        let mut events: Vec<Val> = Vec::new(env);
+        let name = Symbol::new(env, name);
+
+        for (address, topics, data) in env.events().all().iter() {
+            if address == contract_address && Symbol::from_val(env, &topics.get(0).unwrap()) == name {
+                events.push_back(data);
+            }
+        }
+
+        events
+    };
+
+    // One event for all 20 colors
+    let colors_out = contract_events(&env, "colors_out");
+    let event: ColorsOutEvent = colors_out.last().unwrap().into_val(&env);
+
+    assert_eq!(colors_out.len(), 1);
+    assert_eq!(event.owner, u1_address);
+    assert_eq!(event.colors.get(u1_address.clone()).unwrap(), colors_amounts);
+
+    // Scrapes stop at `MaxPaymentCount` (15) colors, the rest comes out of a second call
+    client.glyph_scrape(&Some(u2_address.clone()), &HashType::Glyph(hash), &None, &None);
+
+    let event: ColorsInEvent = contract_events(&env, "colors_in").last().unwrap().into_val(&env);
+
+    assert_eq!(event.owner, u1_address);
+    assert_eq!(event.to, u2_address);
+    assert_eq!(event.colors.get(u1_address.clone()).unwrap().len(), 15);
+
+    client.glyph_scrape(&Some(u2_address.clone()), &HashType::Colors(u1_address.clone(), 1), &None, &None);
+
+    let event: ColorsInEvent = contract_events(&env, "colors_in").last().unwrap().into_val(&env);
+
+    assert_eq!(event.colors.get(u1_address.clone()).unwrap().len(), 5);
+    assert_eq!(client.color_balance(&u2_address, &19, &Some(u1_address.clone())), 2);
+}
+
+#[test]
+fn test_scrape_progress() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+
+    let hash = fixture.mint_image(&u1_address, &[[0x10, 0x20], [0x30, 0x30]]);
+
+    let scrape_completes = || {
+        let name = Symbol::new(env, "glyph_scrape_complete");
+        let mut events: Vec<Val> = Vec::new(env);
+
+        for (address, topics, data) in env.events().all().iter() {
+            if address == client.address && Symbol::from_val(env, &topics.get(0).unwrap()) == name {
+                events.push_back(data);
+            }
+        }
+
+        events
+    };
+
+    // A zero limit would never make progress
+    assert_eq!(
+        client.try_glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &Some(0)),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidAmount)))
+    );
+
+    // Colors come out in color order, two at a time
+    let progress = client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &Some(2));
+    let remaining = ScrapeProgress {
+        draft: Some(1),
+        miners: 1,
+        colors: 1,
+        pixels: 2,
+    };
+
+    assert_eq!(progress, remaining);
+    assert_eq!(client.glyph_scrape_status(&u1_address, &1), remaining);
+    assert_eq!(scrape_completes().len(), 0);
+
+    fixture.assert_colors(&u1_address, &u1_address, 0x20, 1);
+    fixture.assert_colors(&u1_address, &u1_address, 0x30, 0);
+
+    // Limits are capped by `MaxPaymentCount` rather than rejected
+    let progress = client.glyph_scrape(
+        &Some(u2_address.clone()),
+        &HashType::Colors(u1_address.clone(), 1),
+        &None,
+        &Some(u32::MAX),
+    );
+    let done = ScrapeProgress {
+        draft: None,
+        miners: 0,
+        colors: 0,
+        pixels: 0,
+    };
+
+    assert_eq!(progress, done);
+    assert_eq!(client.glyph_scrape_status(&u1_address, &1), done);
+
+    fixture.assert_colors(&u2_address, &u1_address, 0x30, 2);
+
+    let completes = scrape_completes();
+    let event: GlyphScrapeCompleteEvent = completes.last().unwrap().into_val(env);
+
+    assert_eq!(completes.len(), 1);
+    assert_eq!(event.owner, u1_address);
+    assert_eq!(event.to, u2_address);
+}
+
+#[test]
+fn test_scrape_spender() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+
+    let hash = fixture.mint_image(&u1_address, &[[0x10, 0x20], [0x30, 0x30]]);
+
+    client.approve(&u1_address, &u2_address, &hash, &1_000);
+
+    // The remainder would land in a draft only operators can scrape, so a glyph spender has to take it all at once
+    assert_eq!(
+        client.try_glyph_scrape(
+            &Some(u2_address.clone()),
+            &HashType::Glyph(hash.clone()),
+            &Some(u2_address.clone()),
+            &Some(2)
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    // Nothing was left behind so there's no draft to point at
+    let progress = client.glyph_scrape(
+        &Some(u2_address.clone()),
+        &HashType::Glyph(hash),
+        &Some(u2_address.clone()),
+        &Some(3),
+    );
+
+    assert_eq!(
+        progress,
+        ScrapeProgress {
+            draft: None,
+            miners: 0,
+            colors: 0,
+            pixels: 0,
+        }
+    );
+    assert_eq!(client.drafts_of(&u1_address), vec![env]);
+
+    fixture.assert_colors(&u2_address, &u1_address, 0x30, 2);
+}
+
+#[test]
+fn test_drafts() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+
+    fixture.mine(&u1_address, &[0x10, 0x20, 0x30], 10);
+
+    let pixels = |color: u32, indexes: Vec<u32>| map![env, (u1_address.clone(), map![env, (color, indexes)])];
+
+    let draft_5 = Some(MintOptions {
+        draft: Some(5),
+        ..Default::default()
+    });
+
+    // Two builds side by side, `None` is draft 0
+    client.glyph_mint(&u1_address, &None, &pixels(0x10, vec![env, 0, 1]), &None, &None);
+    client.glyph_mint(&u1_address, &None, &pixels(0x20, vec![env, 0]), &None, &draft_5);
+    client.glyph_mint(&u1_address, &None, &pixels(0x20, vec![env, 1]), &None, &draft_5);
+
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 0, 5]);
+    assert_eq!(
+        client.glyph_get(&HashType::Colors(u1_address.clone(), 5)),
+        GlyphType::Colors(pixels(0x20, vec![env, 0, 1]))
+    );
+
+    // Indexes only collide within a draft
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &pixels(0x30, vec![env, 1]), &None, &draft_5),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+
+    let hash = client
+        .glyph_mint(&u1_address, &None, &pixels(0x30, vec![env, 2]), &Some(2), &draft_5)
+        .unwrap();
+
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 0]);
+
+    // Scraping no longer waits on pending builds, the rest of the glyph gets a draft of its own
+    let progress = client.glyph_scrape(&None, &HashType::Glyph(hash), &None, &Some(1));
+
+    assert_eq!(progress.draft, Some(1));
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 0, 1]);
+    assert_eq!(client.glyph_scrape_status(&u1_address, &1), progress);
+    fixture.assert_colors(&u1_address, &u1_address, 0x20, 10);
+    fixture.assert_colors(&u1_address, &u1_address, 0x30, 9);
+
+    // Transferred drafts land in the recipient's next free draft, and only with their say so
+    client.glyph_transfer(&u2_address, &HashType::Colors(u1_address.clone(), 0), &None);
+
+    assert!(env.auths().iter().any(|(address, _)| *address == u2_address));
+
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 1]);
+    assert_eq!(client.drafts_of(&u2_address), vec![env, 1]);
+    assert_eq!(
+        client.glyph_get(&HashType::Colors(u2_address.clone(), 1)),
+        GlyphType::Colors(pixels(0x10, vec![env, 0, 1]))
+    );
+    assert_eq!(
+        client.try_glyph_get(&HashType::Colors(u1_address.clone(), 0)),
+        Err(Ok(Error::NotFound))
+    );
+
+    client.glyph_scrape(&None, &HashType::Colors(u1_address.clone(), 1), &None, &None);
+
+    assert_eq!(client.drafts_of(&u1_address), vec![env]);
+    fixture.assert_colors(&u1_address, &u1_address, 0x30, 10);
+}
+
+#[test]
+fn test_draft_recover_legacy() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+
+    fixture.mine(&u1_address, &[0x10, 0x20], 10);
+
+    let pixels = |color: u32, indexes: Vec<u32>| map![env, (u1_address.clone(), map![env, (color, indexes)])];
+
+    // A build left pending under the key from before drafts, minted into a spare draft so its colors are debited
+    let legacy = |colors: &Map<Address, Map<u32, Vec<u32>>>| {
+        let draft_9 = Some(MintOptions {
+            draft: Some(9),
+            ..Default::default()
+        });
+
+        client.glyph_mint(&u1_address, &None, colors, &None, &draft_9);
+
+        env.as_contract(&client.address, || {
+            remove_draft(env, u1_address.clone(), 9);
+
+            env.storage()
+                .persistent()
+                .set(&StorageKey::Colors(u1_address.clone()), colors);
+        });
+    };
+
+    assert_eq!(
+        client.try_draft_recover_legacy(&u1_address),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+
+    legacy(&pixels(0x10, vec![env, 0, 1]));
+
+    assert_eq!(client.draft_recover_legacy(&u1_address), 0);
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 0]);
+    assert_eq!(
+        client.glyph_get(&HashType::Colors(u1_address.clone(), 0)),
+        GlyphType::Colors(pixels(0x10, vec![env, 0, 1]))
+    );
+
+    // Recovered builds are plain drafts again, scraping refunds the colors
+    client.glyph_scrape(&None, &HashType::Colors(u1_address.clone(), 0), &None, &None);
+
+    fixture.assert_colors(&u1_address, &u1_address, 0x10, 10);
+
+    // With draft 0 in use the old build goes to the next free one
+    client.glyph_mint(&u1_address, &None, &pixels(0x20, vec![env, 0]), &None, &None);
+
+    legacy(&pixels(0x10, vec![env, 2]));
+
+    assert_eq!(client.draft_recover_legacy(&u1_address), 1);
+    assert_eq!(client.drafts_of(&u1_address), vec![env, 0, 1]);
+    assert_eq!(
+        client.try_draft_recover_legacy(&u1_address),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+}
+
+#[test]
+fn test_draft_erase() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+
+    fixture.mine(&u1_address, &[0x10], 5);
+    fixture.mine(&u2_address, &[0x20], 3);
+    client.colors_transfer(&u2_address, &u1_address, &vec![env, (u2_address.clone(), 0x20, 3)]);
+
+    client.glyph_mint(
+        &u1_address,
+        &None,
+        &map![
+            env,
+            (u1_address.clone(), map![env, (0x10, vec![env, 0, 1, 2])]),
+            (u2_address.clone(), map![env, (0x20, vec![env, 3, 4])])
+        ],
+        &None,
+        &None,
+    );
+
+    // Pixels have to be erased from the miner and color they were painted with
+    assert_eq!(
+        client.try_draft_erase(&u1_address, &0, &map![env, (u1_address.clone(), map![env, (0x10, vec![env, 3])])]),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+    assert_eq!(
+        client.try_draft_erase(&u1_address, &0, &map![env, (u2_address.clone(), map![env, (0x10, vec![env, 0])])]),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+
+    client.draft_erase(
+        &u1_address,
+        &0,
+        &map![
+            env,
+            (u1_address.clone(), map![env, (0x10, vec![env, 1])]),
+            (u2_address.clone(), map![env, (0x20, vec![env, 4])])
+        ],
+    );
+
+    assert_eq!(
+        client.glyph_get(&HashType::Colors(u1_address.clone(), 0)),
+        GlyphType::Colors(map![
+            env,
+            (u1_address.clone(), map![env, (0x10, vec![env, 0, 2])]),
+            (u2_address.clone(), map![env, (0x20, vec![env, 3])])
+        ])
+    );
+    fixture.assert_colors(&u1_address, &u1_address, 0x10, 3);
+    fixture.assert_colors(&u1_address, &u2_address, 0x20, 2);
+
+    // Painting over a pixel refunds whatever was there
+    client.draft_paint(&u1_address, &0, &map![env, (u2_address.clone(), map![env, (0x20, vec![env, 0])])]);
+
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(address, topics, _)| {
+            *address == client.address && Symbol::from_val(env, &topics.get(0).unwrap()) == Symbol::new(env, "draft_erase")
+        })
+        .last()
+        .unwrap();
+    let event: DraftEraseEvent = data.into_val(env);
+
+    assert_eq!(event.colors, map![env, (u1_address.clone(), map![env, (0x10, vec![env, 0])])]);
+    assert_eq!(
+        client.glyph_get(&HashType::Colors(u1_address.clone(), 0)),
+        GlyphType::Colors(map![
+            env,
+            (u1_address.clone(), map![env, (0x10, vec![env, 2])]),
+            (u2_address.clone(), map![env, (0x20, vec![env, 3, 0])])
+        ])
+    );
+    fixture.assert_colors(&u1_address, &u1_address, 0x10, 4);
+    fixture.assert_colors(&u1_address, &u2_address, 0x20, 1);
+
+    // Plain mints still refuse to paint over
+    assert_eq!(
+        client.try_glyph_mint(
+            &u1_address,
+            &None,
+            &map![env, (u1_address.clone(), map![env, (0x10, vec![env, 2])])],
+            &None,
+            &None,
+        ),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+
+    // Erasing the last pixel drops the draft
+    client.draft_erase(
+        &u1_address,
+        &0,
+        &map![
+            env,
+            (u1_address.clone(), map![env, (0x10, vec![env, 2])]),
+            (u2_address.clone(), map![env, (0x20, vec![env, 0, 3])])
+        ],
+    );
+
+    assert_eq!(client.drafts_of(&u1_address), vec![env]);
+    fixture.assert_colors(&u1_address, &u1_address, 0x10, 5);
+    fixture.assert_colors(&u1_address, &u2_address, 0x20, 3);
+}
+
+#[test]
+fn test_glyph_preview() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+
+    let image = [[0x10, 0x20, BLANK], [0x30, BLANK, BLANK]];
+    let (_, indexes) = fixture.image_colors(&image);
+    let colors = map![env, (u1_address.clone(), indexes.clone())];
+
+    let preview = client.glyph_preview(&colors, &3, &false);
+
+    assert_eq!(preview.length, 4);
+    assert_eq!(preview.pixels, 3);
+    assert_eq!(preview.status, GlyphStatus::Free);
+
+    // Previewing doesn't spend or write anything
+    fixture.assert_colors(&u1_address, &u1_address, 0x10, 0);
+
+    // Miners aren't part of the hash, transparency is
+    assert_eq!(client.glyph_preview(&map![env, (u2_address.clone(), indexes)], &3, &false).hash, preview.hash);
+    assert_ne!(client.glyph_preview(&colors, &3, &true).hash, preview.hash);
+
+    let hash = fixture.mint_image(&u1_address, &image);
+
+    assert_eq!(hash, preview.hash);
+    assert_eq!(
+        client.glyph_preview(&colors, &3, &false).status,
+        GlyphStatus::Owned(u1_address.clone())
+    );
+
+    client.glyph_transfer(&u2_address, &HashType::Glyph(hash.clone()), &None);
+    client.glyph_scrape(&None, &HashType::Glyph(hash), &None, &None);
+
+    assert_eq!(
+        client.glyph_preview(&colors, &3, &false).status,
+        GlyphStatus::Minted(u1_address.clone())
+    );
+
+    // Same checks as a mint
+    assert_eq!(
+        client.try_glyph_preview(&colors, &0, &false),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidWidth)))
+    );
+    assert_eq!(
+        client.try_glyph_preview(&map![env, (u1_address.clone(), map![env, (0x10, vec![env, 0, 0])])], &3, &false),
+        Err(Ok(soroban_sdk::Error::from(Error::DuplicateIndex)))
+    );
+    assert_eq!(
+        client.try_glyph_preview(&map![env, (u1_address.clone(), map![env, (0x10, vec![env, u32::MAX])])], &3, &false),
+        Err(Ok(soroban_sdk::Error::from(Error::IndexOutOfRange)))
+    );
+}
+
+#[test]
+fn test_glyph_history() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+    let u3_address = fixture.user();
+
+    let image = [[0x10, 0x20], [0x30, BLANK]];
+
+    env.ledger().with_mut(|li| li.sequence_number = 10);
+
+    let hash = fixture.mint_image(&u1_address, &image);
+
+    env.ledger().with_mut(|li| li.sequence_number = 20);
+
+    client.glyph_transfer(&u2_address, &HashType::Glyph(hash.clone()), &None);
+
+    env.ledger().with_mut(|li| li.sequence_number = 30);
+
+    let price = Offer::Asset(fixture.token.address.clone(), 100);
+
+    client.offer_post(&Offer::Glyph(hash.clone()), &price, &None);
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), fixture.token.address.clone(), 100),
+        &Offer::Glyph(hash.clone()),
+        &None,
+    );
+    fixture.assert_owner(&hash, &u3_address);
+
+    env.ledger().with_mut(|li| li.sequence_number = 40);
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    // The default policy keeps the original minter (and their royalties) through a re-mint
+    assert_eq!(client.remint_policy(), RemintPolicy::OriginalMinter);
+    assert_eq!(fixture.mint_image(&u3_address, &image), hash);
+    assert_eq!(client.glyph_minter(&hash), Some(u1_address.clone()));
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    // The re-mint spent u3's own colors so that's what the scrape hands back
+    for color in [0x10, 0x20, 0x30] {
+        fixture.assert_colors(&u3_address, &u1_address, color, 1);
+        fixture.assert_colors(&u3_address, &u3_address, color, 1);
+    }
+
+    client.update_remint_policy(&RemintPolicy::LatestMinter);
+    fixture.mint_image(&u3_address, &image);
+
+    assert_eq!(client.glyph_minter(&hash), Some(u3_address.clone()));
+
+    assert_eq!(
+        client.glyph_history(&hash, &0),
+        vec![
+            env,
+            ProvenanceEntry {
+                ledger: 10,
+                record: Provenance::Mint(u1_address.clone(), u1_address.clone()),
+            },
+            ProvenanceEntry {
+                ledger: 20,
+                record: Provenance::Transfer(u1_address.clone(), u2_address.clone()),
+            },
+            ProvenanceEntry {
+                ledger: 30,
+                record: Provenance::Sale(u2_address.clone(), u3_address.clone(), price),
+            },
+            ProvenanceEntry {
+                ledger: 40,
+                record: Provenance::Scrape(u3_address.clone()),
+            },
+            ProvenanceEntry {
+                ledger: 40,
+                record: Provenance::Remint(u3_address.clone(), u3_address.clone(), u1_address.clone()),
+            },
+            ProvenanceEntry {
+                ledger: 40,
+                record: Provenance::Scrape(u3_address.clone()),
+            },
+            ProvenanceEntry {
+                ledger: 40,
+                record: Provenance::Remint(u3_address.clone(), u3_address.clone(), u3_address.clone()),
+            },
+        ]
+    );
+
+    // Paging
+    assert_eq!(client.glyph_history(&hash, &5).len(), 2);
+    assert_eq!(client.glyph_history(&hash, &7).len(), 0);
+    assert_eq!(client.glyph_history(&hash, &u32::MAX).len(), 0);
+
+    // Nothing was ever recorded for a never minted glyph
+    assert_eq!(client.glyph_history(&BytesN::from_array(env, &[0; 32]), &0).len(), 0);
+    assert_eq!(client.glyph_minter(&BytesN::from_array(env, &[0; 32])), None);
+}
@@ -0,0 +1,700 @@
+#![cfg(test)]
+
+extern crate std;
+
+use crate::{
+    contract::{ColorGlyph, ColorGlyphClient},
+    events::{GlyphMetadataEvent, EVENT_VERSION},
+    testutils::Fixture,
+    types::{Error, Glyph, GlyphMetadata, HashType, MetadataPolicy, MintMetadata, MintOptions, Offer, RenderFormat, StorageKey},
+};
+use soroban_sdk::{
+    map,
+    testutils::{Address as _, Events, Ledger, MockAuth, MockAuthInvoke},
+    token, vec, Address, Bytes, Env, IntoVal, String, Symbol,
+};
+
+#[test]
+fn test_transfer_and_approvals() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    env.ledger().with_mut(|li| li.sequence_number = 100);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 2), (16777215, 2)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (
+                    u1_address.clone(),
+                    map![&env, (0, vec![&env, 0, 3]), (16777215, vec![&env, 1, 2]),]
+                )
+            ],
+            &Some(2),
+            &None,
+        )
+        .unwrap();
+
+    assert_eq!(client.owner_of(&hash), u1_address);
+    assert_eq!(client.balance(&u1_address), 1);
+    assert_eq!(client.balance(&u2_address), 0);
+
+    // Only the owner can transfer without an approval
+    assert_eq!(
+        client.try_transfer_from(&u2_address, &u1_address, &u2_address, &hash),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    client.approve(&u1_address, &u2_address, &hash, &200);
+
+    assert_eq!(client.get_approved(&hash), Some(u2_address.clone()));
+
+    client.transfer_from(&u2_address, &u1_address, &u3_address, &hash);
+
+    assert_eq!(client.owner_of(&hash), u3_address);
+    assert_eq!(client.balance(&u1_address), 0);
+    assert_eq!(client.balance(&u3_address), 1);
+
+    // Approvals are cleared by the transfer
+    assert_eq!(client.get_approved(&hash), None);
+    assert_eq!(
+        client.try_transfer_from(&u2_address, &u3_address, &u2_address, &hash),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    // Operators outlive transfers but not their expiration ledger
+    client.approve_for_all(&u3_address, &u1_address, &150);
+
+    assert!(client.is_approved_for_all(&u3_address, &u1_address));
+
+    env.ledger().with_mut(|li| li.sequence_number = 151);
+
+    assert!(!client.is_approved_for_all(&u3_address, &u1_address));
+    assert_eq!(
+        client.try_transfer_from(&u1_address, &u3_address, &u1_address, &hash),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+    assert_eq!(
+        client.try_approve_for_all(&u3_address, &u1_address, &150),
+        Err(Ok(soroban_sdk::Error::from(Error::InvalidExpiration)))
+    );
+
+    client.transfer(&u3_address, &u2_address, &hash);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(client.balance(&u3_address), 0);
+    assert_eq!(client.balance(&u2_address), 1);
+}
+
+#[test]
+fn test_token_uri() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+            &Some(1),
+            &None,
+        )
+        .unwrap();
+
+    assert_eq!(client.name(), String::from_str(&env, "Colorglyph"));
+    assert_eq!(client.symbol(), String::from_str(&env, "GLYPH"));
+
+    client.update_base_uri(&String::from_str(&env, "https://example.com/"));
+
+    // sha256 of [0, 0, 0, 1]
+    assert_eq!(
+        client.token_uri(&hash),
+        String::from_str(
+            &env,
+            "https://example.com/b40711a88c7039756fb8a73827eabe2c0fe5a0346ca7e0a104adc0fc764f528d"
+        )
+    );
+}
+
+#[test]
+fn test_spender() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let market_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 2)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+            &Some(1),
+            &None,
+        )
+        .unwrap();
+
+    let glyph = Offer::Glyph(hash.clone());
+    let asset = Offer::Asset(token_address.clone(), 100);
+
+    assert_eq!(
+        client.try_offer_post(&glyph, &asset, &Some(market_address.clone())),
+        Err(Ok(Error::NotAuthorized))
+    );
+
+    client.approve(&u1_address, &market_address, &hash, &1_000);
+
+    client.offer_post(&glyph, &asset, &Some(market_address.clone()));
+    client.offers_get(&glyph, &Some(asset.clone()));
+    client.offer_delete(&glyph, &Some(asset.clone()), &Some(market_address.clone()));
+
+    // Approvals are cleared once the spender moves the glyph
+    client.glyph_transfer(&u2_address, &HashType::Glyph(hash.clone()), &Some(market_address.clone()));
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+    assert_eq!(
+        client.try_glyph_scrape(&None, &HashType::Glyph(hash.clone()), &Some(market_address.clone()), &None),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+
+    // Operators can scrape glyphs and handle in progress Colors
+    client.approve_for_all(&u2_address, &market_address, &1_000);
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &Some(market_address.clone()), &None);
+
+    assert_eq!(client.color_balance(&u2_address, &0, &Some(u1_address.clone())), 1);
+
+    client.glyph_mint(
+        &u2_address,
+        &None,
+        &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])],
+        &None,
+        &None,
+    );
+    client.glyph_transfer(
+        &u1_address,
+        &HashType::Colors(u2_address.clone(), 0),
+        &Some(market_address.clone()),
+    );
+
+    assert_eq!(
+        client.try_glyph_scrape(&None, &HashType::Colors(u1_address.clone(), 1), &Some(market_address.clone()), &None),
+        Err(Ok(soroban_sdk::Error::from(Error::NotAuthorized)))
+    );
+}
+
+#[test]
+fn test_metadata() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u3_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    let metadata = GlyphMetadata {
+        title: String::from_str(&env, "Sunrise"),
+        story: String::from_str(&env, "The first light over the harbor"),
+        external_url: String::from_str(&env, "https://example.com/sunrise"),
+        attributes: map![
+            &env,
+            (String::from_str(&env, "palette"), String::from_str(&env, "warm"))
+        ],
+    };
+    let with_metadata = Some(MintOptions {
+        metadata: MintMetadata::Some(metadata.clone()),
+        ..Default::default()
+    });
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0])])];
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+
+    // Metadata only comes with a finished glyph
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &colors, &None, &with_metadata),
+        Err(Ok(soroban_sdk::Error::from(Error::MissingWidth)))
+    );
+
+    let hash = client
+        .glyph_mint(&u1_address, &None, &colors, &Some(1), &with_metadata)
+        .unwrap();
+
+    assert_eq!(client.glyph_metadata(&hash), Some(metadata.clone()));
+    assert_eq!(client.glyph_metadata_policy(&hash), MetadataPolicy::Owner);
+
+    let mut edited = metadata.clone();
+    edited.title = String::from_str(&env, "Sunset");
+
+    client.glyph_metadata_update(&hash, &edited, &None);
+
+    let (_, topics, data) = env.events().all().last().unwrap();
+    let event: GlyphMetadataEvent = data.into_val(&env);
+
+    assert_eq!(topics, (Symbol::new(&env, "metadata_updated"), EVENT_VERSION, hash.clone()).into_val(&env));
+    assert_eq!(event.editor, u1_address);
+    assert_eq!(event.metadata, edited);
+
+    assert_eq!(client.glyph_metadata(&hash), Some(edited.clone()));
+
+    let mut oversized = edited.clone();
+    oversized.title = String::from_str(
+        &env,
+        "The first light over the harbor and the last light over the hills beyond",
+    );
+
+    assert_eq!(
+        client.try_glyph_metadata_update(&hash, &oversized, &None),
+        Err(Ok(soroban_sdk::Error::from(Error::MetadataTooLarge)))
+    );
+
+    // Minter-only edits stay with the minter after a transfer
+    client.glyph_metadata_set_policy(&hash, &MetadataPolicy::Minter, &None);
+    client.transfer(&u1_address, &u2_address, &hash);
+    client.glyph_metadata_update(&hash, &metadata, &None);
+
+    assert_eq!(env.auths()[0].0, u1_address);
+
+    // Nor can the owner switch the policy to get out from under the minter
+    env.mock_auths(&[MockAuth {
+        address: &u2_address,
+        invoke: &MockAuthInvoke {
+            contract: &contract_address,
+            fn_name: "glyph_metadata_set_policy",
+            args: (hash.clone(), MetadataPolicy::Owner, None::<Address>).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+
+    assert!(client
+        .try_glyph_metadata_set_policy(&hash, &MetadataPolicy::Owner, &None)
+        .is_err());
+    assert_eq!(client.glyph_metadata_policy(&hash), MetadataPolicy::Minter);
+
+    env.mock_all_auths();
+
+    // Frozen on sale locks the metadata once an offer is taken
+    client.glyph_metadata_set_policy(&hash, &MetadataPolicy::FrozenOnSale, &None);
+
+    assert_eq!(env.auths()[0].0, u1_address);
+
+    client.glyph_metadata_update(&hash, &edited, &None);
+
+    assert_eq!(env.auths()[0].0, u2_address);
+
+    let glyph = Offer::Glyph(hash.clone());
+
+    client.offer_post(&glyph, &Offer::Asset(token_address.clone(), 100), &None);
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), token_address.clone(), 100),
+        &glyph,
+        &None,
+    );
+
+    assert_eq!(client.owner_of(&hash), u3_address);
+    assert_eq!(client.glyph_metadata_policy(&hash), MetadataPolicy::Frozen);
+    assert_eq!(
+        client.try_glyph_metadata_update(&hash, &metadata, &None),
+        Err(Ok(soroban_sdk::Error::from(Error::MetadataFrozen)))
+    );
+    assert_eq!(
+        client.try_glyph_metadata_set_policy(&hash, &MetadataPolicy::Owner, &None),
+        Err(Ok(soroban_sdk::Error::from(Error::MetadataFrozen)))
+    );
+
+    // Scraping clears it all out
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    assert_eq!(client.glyph_metadata(&hash), None);
+    assert_eq!(client.glyph_metadata_policy(&hash), MetadataPolicy::Owner);
+}
+
+#[test]
+fn test_render() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 3), (0xff0000, 1)], &None, &None, &None);
+
+    // 3x2 with one pixel left uncolored
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (
+                    u1_address.clone(),
+                    map![&env, (0, vec![&env, 0, 1, 5]), (0xff0000, vec![&env, 3])]
+                )
+            ],
+            &Some(3),
+            &None,
+        )
+        .unwrap();
+
+    let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 3 2\" shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/><rect x=\"0\" y=\"0\" width=\"2\" height=\"1\" fill=\"#000000\"/><rect x=\"0\" y=\"1\" width=\"1\" height=\"1\" fill=\"#ff0000\"/><rect x=\"2\" y=\"1\" width=\"1\" height=\"1\" fill=\"#000000\"/></svg>";
+
+    assert_eq!(
+        client.glyph_render(&hash, &RenderFormat::Svg),
+        Bytes::from_slice(&env, svg.as_bytes())
+    );
+
+    let bmp = client.glyph_render(&hash, &RenderFormat::Bmp);
+
+    assert_eq!(bmp.len(), 54 + 12 * 2);
+    assert_eq!(bmp.slice(..2), Bytes::from_slice(&env, b"BM"));
+    // Bottom row first in BGR, padded out to 12 bytes
+    assert_eq!(
+        bmp.slice(54..66),
+        Bytes::from_slice(&env, &[0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0])
+    );
+
+    // No base uri serves the SVG on chain
+    assert_eq!(
+        client.token_uri(&hash),
+        String::from_str(
+            &env,
+            "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHZpZXdCb3g9IjAgMCAzIDIiIHNoYXBlLXJlbmRlcmluZz0iY3Jpc3BFZGdlcyI+PHJlY3Qgd2lkdGg9IjEwMCUiIGhlaWdodD0iMTAwJSIgZmlsbD0iI2ZmZmZmZiIvPjxyZWN0IHg9IjAiIHk9IjAiIHdpZHRoPSIyIiBoZWlnaHQ9IjEiIGZpbGw9IiMwMDAwMDAiLz48cmVjdCB4PSIwIiB5PSIxIiB3aWR0aD0iMSIgaGVpZ2h0PSIxIiBmaWxsPSIjZmYwMDAwIi8+PHJlY3QgeD0iMiIgeT0iMSIgd2lkdGg9IjEiIGhlaWdodD0iMSIgZmlsbD0iIzAwMDAwMCIvPjwvc3ZnPg=="
+        )
+    );
+
+    // A stored glyph without a width renders empty rather than dividing by zero
+    env.as_contract(&contract_address, || {
+        let glyph_key = StorageKey::Glyph(hash.clone());
+        let mut glyph: Glyph = env.storage().persistent().get(&glyph_key).unwrap();
+
+        glyph.width = 0;
+
+        env.storage().persistent().set(&glyph_key, &glyph);
+    });
+
+    assert_eq!(
+        client.glyph_render(&hash, &RenderFormat::Svg),
+        Bytes::from_slice(
+            &env,
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 0 0\" shape-rendering=\"crispEdges\"><rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/></svg>"
+        )
+    );
+
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    assert_eq!(
+        client.try_glyph_render(&hash, &RenderFormat::Svg),
+        Err(Ok(soroban_sdk::Error::from(Error::NotFound)))
+    );
+}
+
+#[test]
+fn test_render_sizes() {
+    let fixture = Fixture::builder().build();
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+
+    // Checkerboards are all runs of one so the uri outgrows the smaller buffers
+    for size in [1, 8, 24] {
+        let image: std::vec::Vec<std::vec::Vec<u32>> = (0..size)
+            .map(|y| (0..size).map(|x| if (x + y) % 2 == 0 { 0 } else { 0xffffff - y }).collect())
+            .collect();
+
+        let hash = fixture.mint_image(&u1_address, &image);
+        let svg = client.glyph_render(&hash, &RenderFormat::Svg);
+        let uri = client.token_uri(&hash);
+
+        assert_eq!(uri.len(), 26 + svg.len().div_ceil(3) * 4);
+    }
+}
+
+#[test]
+fn test_transfer_clears_offers() {
+    let fixture = Fixture::builder().build();
+    let client = &fixture.client;
+
+    let u1_address = fixture.user();
+    let u2_address = fixture.user();
+    let u3_address = fixture.user();
+
+    let hash = fixture.mint_image(&u1_address, &[[0x10, 0x20]]);
+    let glyph = Offer::Glyph(hash.clone());
+    let asset = Offer::Asset(fixture.token.address.clone(), 100);
+
+    client.offer_post(&glyph, &asset, &None);
+    client.transfer(&u1_address, &u2_address, &hash);
+
+    // u1's price doesn't bind u2
+    assert_eq!(client.try_offers_get(&glyph, &Some(asset.clone())), Err(Ok(Error::OfferNotFound)));
+
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), fixture.token.address.clone(), 100),
+        &glyph,
+        &None,
+    );
+
+    fixture.assert_owner(&hash, &u2_address);
+    fixture.assert_balance(&u3_address, 10_000 - 100); // escrowed as a buy offer instead
+
+    // Same through `glyph_transfer`
+    client.offer_post(&glyph, &Offer::Asset(fixture.token.address.clone(), 200), &None);
+    client.glyph_transfer(&u1_address, &HashType::Glyph(hash.clone()), &None);
+
+    assert_eq!(
+        client.try_offers_get(&glyph, &Some(Offer::Asset(fixture.token.address.clone(), 200))),
+        Err(Ok(Error::OfferNotFound))
+    );
+}
+
+#[test]
+fn test_spender_offers_lapse() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+    let token = fixture.token.address.clone();
+
+    let alice = fixture.user();
+    let bob = fixture.user();
+    let market = fixture.user();
+
+    let hash = fixture.mint_image(&alice, &[[0x10]]);
+    let glyph = Offer::Glyph(hash.clone());
+
+    client.approve(&alice, &market, &hash, &1_000);
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 100), &Some(market.clone()));
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 200), &None);
+
+    // Revoking the approval takes the market's offer down with it, not the owner's
+    client.approve(&alice, &market, &hash, &0);
+
+    assert_eq!(
+        client.try_offers_get(&glyph, &Some(Offer::Asset(token.clone(), 100))),
+        Err(Ok(Error::OfferNotFound))
+    );
+    client.offers_get(&glyph, &Some(Offer::Asset(token.clone(), 200)));
+
+    client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 100), &glyph, &None);
+
+    fixture.assert_owner(&hash, &alice);
+
+    // An operator's offers lapse when the operator approval expires
+    env.ledger().with_mut(|li| li.sequence_number = 10);
+
+    client.approve_for_all(&alice, &market, &50);
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 300), &Some(market.clone()));
+    client.offers_get(&glyph, &Some(Offer::Asset(token.clone(), 300)));
+
+    env.ledger().with_mut(|li| li.sequence_number = 51);
+
+    client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 300), &glyph, &None);
+
+    fixture.assert_owner(&hash, &alice);
+
+    // The owner's own offer still fills
+    client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 200), &glyph, &None);
+
+    fixture.assert_owner(&hash, &bob);
+}
+
+#[test]
+fn test_transparency() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    // Contract
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    // Token
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    // Accounts
+    let u1_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // Tests
+    client.colors_mine(&u1_address, &map![&env, (0, 6), (0xffffff, 1)], &None, &None, &None);
+
+    // 3x1 with the middle pixel left uncolored
+    let colors = map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0, 2])])];
+    let transparent = Some(MintOptions {
+        transparent: true,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &colors, &None, &transparent),
+        Err(Ok(soroban_sdk::Error::from(Error::MissingWidth)))
+    );
+
+    let opaque_hash = client
+        .glyph_mint(&u1_address, &None, &colors, &Some(3), &None)
+        .unwrap();
+    let hash = client
+        .glyph_mint(&u1_address, &None, &colors, &Some(3), &transparent)
+        .unwrap();
+    let white_hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (u1_address.clone(), map![&env, (0, vec![&env, 0, 2]), (0xffffff, vec![&env, 1])])
+            ],
+            &Some(3),
+            &transparent,
+        )
+        .unwrap();
+
+    // Blank and white only differ by the mask
+    assert_ne!(hash, opaque_hash);
+    assert_ne!(hash, white_hash);
+
+    // rgb, mask 0b101, one byte of padding, u32 width, version 2
+    assert_eq!(
+        hash,
+        env.crypto().sha256(&Bytes::from_slice(
+            &env,
+            &[0, 0, 0, 255, 255, 255, 0, 0, 0, 0b101, 0, 0, 0, 0, 3, 0, 2]
+        ))
+    );
+
+    assert!(client.glyph_transparent(&hash));
+    assert!(!client.glyph_transparent(&opaque_hash));
+
+    let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 3 1\" shape-rendering=\"crispEdges\"><rect x=\"0\" y=\"0\" width=\"1\" height=\"1\" fill=\"#000000\"/><rect x=\"2\" y=\"0\" width=\"1\" height=\"1\" fill=\"#000000\"/></svg>";
+
+    assert_eq!(
+        client.glyph_render(&hash, &RenderFormat::Svg),
+        Bytes::from_slice(&env, svg.as_bytes())
+    );
+
+    let bmp = client.glyph_render(&hash, &RenderFormat::Bmp);
+
+    assert_eq!(bmp.len(), 122 + 12);
+    assert_eq!(
+        bmp.slice(122..),
+        Bytes::from_slice(&env, &[0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 255])
+    );
+
+    // The flag goes with the hash through a scrape and remint
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
+
+    assert!(client.glyph_transparent(&hash));
+    assert_eq!(
+        client.glyph_mint(&u1_address, &None, &colors, &Some(3), &transparent),
+        Some(hash)
+    );
+}
@@ -4,11 +4,18 @@ use std::println;
 extern crate std;
 
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{map, testutils::Address as _, token, vec, Address, Env, Map, Vec};
+use soroban_sdk::{
+    map,
+    testutils::{Address as _, Events},
+    token, vec, Address, Env, IntoVal, Map, Symbol, Val, Vec,
+};
 
 use crate::{
     contract::{ColorGlyph, ColorGlyphClient},
-    types::{Error, HashType, Offer, StorageKey},
+    events::{GlyphMintEvent, GlyphTransferEvent, OfferFillEvent, OfferPostEvent, RoyaltiesPaidEvent, EVENT_VERSION},
+    royalties::MAX_SALE_PAYMENTS,
+    testutils::{Fixture, BLANK},
+    types::{Error, Glyph, HashType, Offer, StorageKey},
 };
 
 const ITERS: i128 = 10i128;
@@ -50,7 +57,7 @@ fn test_self_purchase() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = client
         .glyph_mint(
@@ -58,6 +65,7 @@ fn test_self_purchase() {
             &None,
             &map![&env, (u1_address.clone(), colors_indexes.clone())],
             &Some(16),
+            &None,
         )
         .unwrap();
 
@@ -67,21 +75,155 @@ fn test_self_purchase() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u1_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&glyph, &asset, &None);
+
+    assert_eq!(
+        client.try_offer_post(&asset_sell, &glyph, &None),
+        Err(Ok(Error::SelfTrade))
+    );
+    assert_eq!(
+        client.try_offer_post(&glyph, &glyph, &None),
+        Err(Ok(Error::SelfTrade))
+    );
+    assert_eq!(
+        client.try_offers_get(&glyph, &Some(glyph.clone())),
+        Err(Ok(Error::OfferNotFound))
+    );
+
+    // The sell offer is still open for everyone else
+    client.offers_get(&glyph, &Some(asset));
+    assert_eq!(client.owner_of(&hash), u1_address);
+}
+
+#[test]
+fn test_sale_limits() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
 
-    client.offer_post(&glyph, &glyph);
+    client.initialize(&u1_address, &token_address, &fee_address);
 
-    client.offers_get(&glyph, &Some(glyph.clone()));
+    assert_eq!(
+        client.try_initialize(&u1_address, &token_address, &fee_address),
+        Err(Ok(soroban_sdk::Error::from(Error::AlreadyInitialized)))
+    );
 
-    client.offer_post(&glyph, &glyph.clone());
+    // Royalties can't add up to more than the sale
+    assert_eq!(
+        client.try_update(&None, &None, &None, &None, &None, &Some(60), &Some(41)),
+        Err(Ok(soroban_sdk::Error::from(Error::RoyaltyOverflow)))
+    );
+
+    client.colors_mine(&u1_address, &map![&env, (0, 1), (1, 1)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![&env, (u1_address.clone(), map![&env, (0, vec![&env, 0]), (1, vec![&env, 1])])],
+            &Some(2),
+            &None,
+        )
+        .unwrap();
+
+    let glyph = Offer::Glyph(hash.clone());
+    let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), 100);
+
+    client.offer_post(&glyph, &Offer::Asset(token_address.clone(), 100), &None);
 
     assert_eq!(
-        client.try_offers_get(&glyph, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        client.try_offer_post(&Offer::AssetSell(u2_address.clone(), token_address.clone(), 0), &glyph, &None),
+        Err(Ok(Error::InvalidAmount))
     );
 
-    // NOTE self purchases are possible. Not sure how I feel about this. Probably fine?
+    // The scrape batch size has nothing to do with what a sale can pay
+    client.update(&None, &None, &None, &None, &Some(2), &None, &None);
+    client.offer_post(&asset_sell, &glyph, &None);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+
+    // Glyphs with more miners than a sale could pay can't be minted
+    let mut colors: Map<Address, Map<u32, Vec<u32>>> = Map::new(&env);
+
+    for i in 0..MAX_SALE_PAYMENTS - 1 {
+        let miner = Address::generate(&env);
+
+        client.colors_mine(&u1_address, &map![&env, (i, 1)], &Some(miner.clone()), &None, &None);
+        colors.set(miner, map![&env, (i, vec![&env, i])]);
+    }
+
+    assert_eq!(
+        client.try_glyph_mint(&u1_address, &None, &colors, &Some(4), &None),
+        Err(Ok(soroban_sdk::Error::from(Error::PaymentCapReached)))
+    );
+
+    colors.remove(colors.keys().last().unwrap());
+
+    let hash = client
+        .glyph_mint(&u1_address, &None, &colors, &Some(4), &None)
+        .unwrap();
+
+    client.offer_post(&Offer::Glyph(hash.clone()), &Offer::Asset(token_address.clone(), 100), &None);
+    client.offer_post(&asset_sell, &Offer::Glyph(hash.clone()), &None);
+
+    assert_eq!(client.owner_of(&hash), u2_address);
+
+    // Glyphs minted before the cap can have more miners, payees are counted once merged (and only if they're owed something)
+    let u3_address = Address::generate(&env);
+
+    token_admin_client.mint(&u3_address, &10_000);
+
+    let add_miner = |miner: &Address, index: u32| {
+        env.as_contract(&contract_address, || {
+            let glyph_key = StorageKey::Glyph(hash.clone());
+            let mut glyph: Glyph = env.storage().persistent().get(&glyph_key).unwrap();
+
+            glyph.colors.set(miner.clone(), map![&env, (index, vec![&env, index])]);
+            glyph.length += 1;
+
+            env.storage().persistent().set(&glyph_key, &glyph);
+        });
+    };
+
+    // The minter as a 14th miner is still one payment
+    add_miner(&u1_address, 13);
+
+    client.offer_post(&Offer::Glyph(hash.clone()), &Offer::Asset(token_address.clone(), 1_500), &None);
+    client.offer_post(
+        &Offer::AssetSell(u3_address.clone(), token_address.clone(), 1_500),
+        &Offer::Glyph(hash.clone()),
+        &None,
+    );
+
+    assert_eq!(client.owner_of(&hash), u3_address);
+
+    add_miner(&Address::generate(&env), 14);
+
+    client.offer_post(&Offer::Glyph(hash.clone()), &Offer::Asset(token_address.clone(), 1_500), &None);
+
+    assert_eq!(
+        client.try_offer_post(
+            &Offer::AssetSell(u2_address.clone(), token_address.clone(), 1_500),
+            &Offer::Glyph(hash.clone()),
+            &None,
+        ),
+        Err(Ok(Error::PaymentCapReached))
+    );
 }
 
 #[test]
@@ -121,7 +263,7 @@ fn test_sell_scrape_buy() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = client
         .glyph_mint(
@@ -129,6 +271,7 @@ fn test_sell_scrape_buy() {
             &None,
             &map![&env, (u1_address.clone(), colors_indexes.clone())],
             &Some(16),
+            &None,
         )
         .unwrap();
 
@@ -138,12 +281,12 @@ fn test_sell_scrape_buy() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
-    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()));
+    client.glyph_scrape(&None, &HashType::Glyph(hash.clone()), &None, &None);
 
     assert_eq!(
-        client.try_glyph_get(&HashType::Colors(u1_address.clone())),
+        client.try_glyph_get(&HashType::Colors(u1_address.clone(), 1)),
         Err(Ok(Error::NotFound))
     );
 
@@ -159,10 +302,10 @@ fn test_sell_scrape_buy() {
 
     assert_eq!(
         client.try_offers_get(&glyph, &None),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     client.offers_get(&asset, &Some(glyph.clone()));
 
@@ -172,19 +315,20 @@ fn test_sell_scrape_buy() {
             &None,
             &map![&env, (u1_address.clone(), colors_indexes)],
             &Some(16),
+            &None,
         )
         .unwrap();
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(
         client.try_offers_get(&glyph, &Some(asset)),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     env.as_contract(&contract_address, || {
@@ -235,7 +379,7 @@ fn test_dupe() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     let hash = client
         .glyph_mint(
@@ -243,6 +387,7 @@ fn test_dupe() {
             &None,
             &map![&env, (u1_address.clone(), colors_indexes)],
             &Some(16),
+            &None,
         )
         .unwrap();
 
@@ -252,20 +397,20 @@ fn test_dupe() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     assert_eq!(
-        client.try_offer_post(&glyph, &asset),
-        Err(Ok(Error::NotEmpty))
+        client.try_offer_post(&glyph, &asset, &None),
+        Err(Ok(Error::OfferExists))
     );
 
-    client.offer_delete(&glyph, &None); // <- delete all open glyph sell offers
+    client.offer_delete(&glyph, &None, &None); // <- delete all open glyph sell offers
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     assert_eq!(
-        client.try_offer_post(&asset_sell, &glyph),
-        Err(Ok(Error::NotEmpty))
+        client.try_offer_post(&asset_sell, &glyph, &None),
+        Err(Ok(Error::OfferExists))
     );
 }
 
@@ -309,7 +454,7 @@ fn test_buy_glyph() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()));
+    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()), &None);
 
     // println!("{:?}\n", colors_indexes);
 
@@ -318,10 +463,11 @@ fn test_buy_glyph() {
         &None,
         &map![&env, (u3_address.clone(), colors_indexes)],
         &None,
+        &None,
     );
 
     let hash = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     println!("{:?}\n", hash);
@@ -332,7 +478,7 @@ fn test_buy_glyph() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
     client.offers_get(&asset_sell, &Some(glyph.clone())); // User 2 is selling
     client.offers_get(&asset, &Some(glyph.clone())); // Someone is selling
 
@@ -342,10 +488,10 @@ fn test_buy_glyph() {
             &Offer::AssetSell(u1_address.clone(), token_address.clone(), amount),
             &Some(glyph.clone())
         ),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     // env.budget().print();
 
@@ -361,12 +507,12 @@ fn test_buy_glyph() {
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(
         client.try_offers_get(&glyph, &Some(asset)),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(token_client.balance(&fee_address), 10i128);
@@ -417,17 +563,18 @@ fn test_sell_glyph() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()));
+    client.colors_mine(&u3_address, &color_amount, &None, &Some(u1_address.clone()), &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u3_address.clone(), colors_indexes)],
         &None,
+        &None,
     );
 
     let hash = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     env.budget().reset_default();
@@ -438,9 +585,9 @@ fn test_sell_glyph() {
     let asset = Offer::Asset(token_address.clone(), amount);
     let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     env.as_contract(&contract_address, || {
         let res = env
@@ -454,12 +601,12 @@ fn test_sell_glyph() {
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     // env.logger().print();
@@ -513,30 +660,32 @@ fn test_swap_glyph() {
         colors_b_amount.set(hex_b as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &colors_a_amount, &None, &None);
+    client.colors_mine(&u1_address, &colors_a_amount, &None, &None, &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u1_address.clone(), colors_a_indexes)],
         &None,
+        &None,
     );
 
     let hash_a = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
-    client.colors_mine(&u2_address, &colors_b_amount, &None, &None);
+    client.colors_mine(&u2_address, &colors_b_amount, &None, &None, &None);
 
     client.glyph_mint(
         &u2_address,
         &None,
         &map![&env, (u2_address.clone(), colors_b_indexes)],
         &None,
+        &None,
     );
 
     let hash_b = client
-        .glyph_mint(&u2_address, &None, &map!(&env), &Some(16))
+        .glyph_mint(&u2_address, &None, &map!(&env), &Some(16), &None)
         .unwrap();
 
     env.budget().reset_default();
@@ -545,9 +694,9 @@ fn test_swap_glyph() {
     let glyph_1 = Offer::Glyph(hash_a.clone());
     let glyph_2 = Offer::Glyph(hash_b.clone());
 
-    client.offer_post(&glyph_1, &glyph_2);
+    client.offer_post(&glyph_1, &glyph_2, &None);
 
-    client.offer_post(&glyph_2, &glyph_1);
+    client.offer_post(&glyph_2, &glyph_1, &None);
 
     env.as_contract(&contract_address, || {
         let res_a = env
@@ -567,14 +716,17 @@ fn test_swap_glyph() {
         assert_eq!(res_b, u1_address);
     });
 
+    assert_eq!(client.glyphs_of(&u1_address, &0, &10), vec![&env, hash_b.clone()]);
+    assert_eq!(client.glyphs_of(&u2_address, &0, &10), vec![&env, hash_a.clone()]);
+
     assert_eq!(
         client.try_offers_get(&glyph_1, &Some(glyph_2.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(
         client.try_offers_get(&glyph_2, &Some(glyph_1)),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 }
 
@@ -596,9 +748,11 @@ fn test_rm_glyph_buy() {
 
     // Accounts
     let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
     let fee_address = Address::generate(&env);
 
     token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
 
     client.initialize(&u1_address, &token_address, &fee_address);
 
@@ -615,17 +769,18 @@ fn test_rm_glyph_buy() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u1_address.clone(), colors_indexes)],
         &None,
+        &None,
     );
 
     let hash = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     env.budget().reset_default();
@@ -634,26 +789,26 @@ fn test_rm_glyph_buy() {
     let amount: i128 = 1;
     let glyph = Offer::Glyph(hash.clone());
     let asset = Offer::Asset(token_address.clone(), amount);
-    let asset_sell = Offer::AssetSell(u1_address.clone(), token_address.clone(), amount);
+    let asset_sell = Offer::AssetSell(u2_address.clone(), token_address.clone(), amount);
 
-    client.offer_post(&asset_sell, &glyph);
+    client.offer_post(&asset_sell, &glyph, &None);
 
     assert_eq!(token_client.balance(&contract_address), 1i128);
 
-    assert_eq!(token_client.balance(&u1_address), 9_989i128);
+    assert_eq!(token_client.balance(&u2_address), 9_999i128);
 
     client.offers_get(&asset, &Some(glyph.clone()));
 
-    client.offer_delete(&asset_sell, &Some(glyph.clone()));
+    client.offer_delete(&asset_sell, &Some(glyph.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&asset, &Some(glyph.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(token_client.balance(&contract_address), 0i128);
 
-    assert_eq!(token_client.balance(&u1_address), 9990i128);
+    assert_eq!(token_client.balance(&u2_address), 10_000i128);
 }
 
 #[test]
@@ -693,17 +848,18 @@ fn test_rm_glyph_sell() {
         color_amount.set(hex as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &color_amount, &None, &None);
+    client.colors_mine(&u1_address, &color_amount, &None, &None, &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u1_address.clone(), colors_indexes)],
         &None,
+        &None,
     );
 
     let hash = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     env.budget().reset_default();
@@ -712,15 +868,15 @@ fn test_rm_glyph_sell() {
     let glyph = Offer::Glyph(hash.clone());
     let asset = Offer::Asset(token_address.clone(), 1i128);
 
-    client.offer_post(&glyph, &asset);
+    client.offer_post(&glyph, &asset, &None);
 
     client.offers_get(&glyph, &Some(asset.clone()));
 
-    client.offer_delete(&glyph, &Some(asset.clone()));
+    client.offer_delete(&glyph, &Some(asset.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&glyph, &Some(asset.clone())),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(token_client.balance(&contract_address), 0i128);
@@ -772,17 +928,18 @@ fn test_rm_glyph_swap() {
         colors_b_amount.set(hex_b as u32, 1);
     }
 
-    client.colors_mine(&u1_address, &colors_a_amount, &None, &None);
+    client.colors_mine(&u1_address, &colors_a_amount, &None, &None, &None);
 
     client.glyph_mint(
         &u1_address,
         &None,
         &map![&env, (u1_address.clone(), colors_a_indexes)],
         &None,
+        &None,
     );
 
     let hash_a = client
-        .glyph_mint(&u1_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u1_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     client.colors_mine(
@@ -790,6 +947,7 @@ fn test_rm_glyph_swap() {
         &colors_b_amount,
         &None,
         &Some(u2_address.clone()),
+        &None,
     );
 
     client.glyph_mint(
@@ -797,10 +955,11 @@ fn test_rm_glyph_swap() {
         &None,
         &map![&env, (u1_address.clone(), colors_b_indexes)],
         &None,
+        &None,
     );
 
     let hash_b = client
-        .glyph_mint(&u2_address, &None, &map![&env], &Some(16))
+        .glyph_mint(&u2_address, &None, &map![&env], &Some(16), &None)
         .unwrap();
 
     env.budget().reset_default();
@@ -809,18 +968,324 @@ fn test_rm_glyph_swap() {
     let glyph_a = Offer::Glyph(hash_a.clone());
     let glyph_b = Offer::Glyph(hash_b.clone());
 
-    client.offer_post(&glyph_a, &glyph_b);
+    client.offer_post(&glyph_a, &glyph_b, &None);
 
     client.offers_get(&glyph_a, &Some(glyph_b.clone()));
 
-    client.offer_delete(&glyph_a, &Some(glyph_b.clone()));
+    client.offer_delete(&glyph_a, &Some(glyph_b.clone()), &None);
 
     assert_eq!(
         client.try_offers_get(&glyph_a, &Some(glyph_b)),
-        Err(Ok(Error::NotFound))
+        Err(Ok(Error::OfferNotFound))
     );
 
     assert_eq!(token_client.balance(&contract_address), 0i128);
 
     assert_eq!(token_client.balance(&u1_address), 9980i128);
 }
+
+#[test]
+fn test_events() {
+    let env = Env::default();
+
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let contract_address = env.register_contract(None, ColorGlyph);
+    let client = ColorGlyphClient::new(&env, &contract_address);
+
+    let token_admin = Address::generate(&env);
+    let token_address = env.register_stellar_asset_contract(token_admin.clone());
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_address);
+
+    let u1_address = Address::generate(&env);
+    let u2_address = Address::generate(&env);
+    let u3_address = Address::generate(&env);
+    let u4_address = Address::generate(&env);
+    let fee_address = Address::generate(&env);
+
+    token_admin_client.mint(&u1_address, &10_000);
+    token_admin_client.mint(&u2_address, &10_000);
+    token_admin_client.mint(&u4_address, &10_000);
+
+    client.initialize(&u1_address, &token_address, &fee_address);
+
+    // u2 mines a color straight into u1's balance
+    client.colors_mine(&u1_address, &map![&env, (0, 1)], &None, &None, &None);
+    client.colors_mine(&u2_address, &map![&env, (1, 1)], &None, &Some(u1_address.clone()), &None);
+
+    let hash = client
+        .glyph_mint(
+            &u1_address,
+            &None,
+            &map![
+                &env,
+                (u1_address.clone(), map![&env, (0, vec![&env, 0])]),
+                (u2_address.clone(), map![&env, (1, vec![&env, 1])])
+            ],
+            &Some(2),
+            &None,
+        )
+        .unwrap();
+
+    let contract_events = |env: &Env| {
+        let mut events: Vec<(Vec<Val>, Val)> = Vec::new(env);
+
+        for (address, topics, data) in env.events().all().iter() {
+            if address == contract_address {
+                events.push_back((topics, data));
+            }
+        }
+
+        events
+    };
+
+    let (topics, data) = contract_events(&env).last().unwrap();
+    let event: GlyphMintEvent = data.into_val(&env);
+
+    assert_eq!(topics, (Symbol::new(&env, "glyph_mint"), EVENT_VERSION, hash.clone()).into_val(&env));
+    assert_eq!(event.owner, u1_address);
+    assert_eq!(event.width, 2);
+
+    // Transfers carry both parties
+    client.transfer(&u1_address, &u3_address, &hash);
+
+    let (topics, data) = contract_events(&env).last().unwrap();
+    let event: GlyphTransferEvent = data.into_val(&env);
+
+    assert_eq!(
+        topics,
+        (Symbol::new(&env, "glyph_transfer"), EVENT_VERSION, HashType::Glyph(hash.clone())).into_val(&env)
+    );
+    assert_eq!(event.from, u1_address);
+    assert_eq!(event.to, u3_address);
+
+    let glyph = Offer::Glyph(hash.clone());
+    let asset = Offer::Asset(token_address.clone(), 100);
+
+    client.offer_post(&glyph, &asset, &None);
+
+    let (_, data) = contract_events(&env).last().unwrap();
+    let event: OfferPostEvent = data.into_val(&env);
+
+    assert_eq!(event.owner, u3_address);
+    assert_eq!(event.buy, asset);
+
+    // Fills are an `offer_fill`, not another `offer_post`, and royalties are summed per payee
+    client.offer_post(
+        &Offer::AssetSell(u4_address.clone(), token_address.clone(), 100),
+        &glyph,
+        &None,
+    );
+
+    let events = contract_events(&env);
+    let (topics, data) = events.get(events.len() - 2).unwrap();
+    let payout: RoyaltiesPaidEvent = data.into_val(&env);
+
+    assert_eq!(topics, (Symbol::new(&env, "royalties_paid"), EVENT_VERSION, hash.clone()).into_val(&env));
+
+    let (topics, data) = events.last().unwrap();
+    let fill: OfferFillEvent = data.into_val(&env);
+
+    assert_eq!(payout.payer, u4_address);
+    assert_eq!(payout.seller, u3_address);
+    assert_eq!(payout.breakdown.payouts, map![&env, (u1_address.clone(), 4), (u2_address.clone(), 1)]);
+    assert_eq!(payout.breakdown.proceeds, 95);
+    assert_eq!(topics, (Symbol::new(&env, "offer_fill"), EVENT_VERSION, hash.clone()).into_val(&env));
+    assert_eq!(
+        fill,
+        OfferFillEvent {
+            hash: hash.clone(),
+            seller: u3_address.clone(),
+            buyer: u4_address.clone(),
+            price: asset,
+            proceeds: 95,
+        }
+    );
+}
+
+#[test]
+fn test_fixture_sale() {
+    let fixture = Fixture::builder().build();
+
+    let alice = fixture.user();
+    let bob = fixture.user();
+
+    let hash = fixture.mint_image(
+        &alice,
+        &[
+            [0xff0000, 0x00ff00, BLANK],
+            [0x0000ff, 0xff0000, 0x00ff00],
+        ],
+    );
+
+    fixture.assert_owner(&hash, &alice);
+    fixture.assert_balance(&alice, 10_000 - 5);
+
+    let glyph = Offer::Glyph(hash.clone());
+
+    fixture.client.offer_post(&glyph, &Offer::Asset(fixture.token.address.clone(), 100), &None);
+    fixture.client.offer_post(
+        &Offer::AssetSell(bob.clone(), fixture.token.address.clone(), 100),
+        &glyph,
+        &None,
+    );
+
+    fixture.assert_owner(&hash, &bob);
+    fixture.assert_balance(&alice, 10_000 - 5 + 100);
+    fixture.assert_balance(&bob, 10_000 - 100);
+
+    fixture.client.glyph_scrape(&None, &HashType::Glyph(hash), &None, &None);
+
+    fixture.assert_colors(&bob, &alice, 0xff0000, 2);
+    fixture.assert_colors(&bob, &alice, 0x00ff00, 2);
+}
+
+#[test]
+fn test_royalties() {
+    let fixture = Fixture::builder().build();
+    let env = &fixture.env;
+    let client = &fixture.client;
+    let token = fixture.token.address.clone();
+
+    let alice = fixture.user(); // minter, also mines a pixel
+    let bob = fixture.user();
+    let carol = fixture.user();
+    let dave = fixture.user();
+
+    client.update(&None, &None, &None, &None, &None, &Some(10), &Some(30));
+
+    client.colors_mine(&bob, &map![env, (0x10, 1)], &None, &Some(alice.clone()), &None);
+    client.colors_mine(&carol, &map![env, (0x20, 2)], &None, &Some(alice.clone()), &None);
+    client.colors_mine(&alice, &map![env, (0x30, 1)], &None, &None, &None);
+
+    let hash = client
+        .glyph_mint(
+            &alice,
+            &None,
+            &map![
+                env,
+                (bob.clone(), map![env, (0x10, vec![env, 0])]),
+                (carol.clone(), map![env, (0x20, vec![env, 1, 2])]),
+                (alice.clone(), map![env, (0x30, vec![env, 3])])
+            ],
+            &Some(4),
+            &None,
+        )
+        .unwrap();
+
+    let glyph = Offer::Glyph(hash.clone());
+    let balances = |users: &[&Address]| users.iter().map(|user| fixture.token.balance(user)).collect::<std::vec::Vec<_>>();
+    let last_payout = || -> RoyaltiesPaidEvent {
+        let events = env.events().all();
+        let (_, _, data) = events.get(events.len() - 2).unwrap();
+
+        data.into_val(env)
+    };
+
+    // Bought directly, every cut rounds down and the seller's own cuts come with their proceeds in one transfer
+    let before = balances(&[&alice, &bob, &carol, &dave]);
+
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 101), &None);
+    client.offer_post(&Offer::AssetSell(dave.clone(), token.clone(), 101), &glyph, &None);
+
+    let payout = last_payout();
+
+    assert_eq!(payout.payer, dave);
+    assert_eq!(payout.seller, alice);
+    assert_eq!(
+        payout.breakdown.payouts,
+        map![env, (alice.clone(), 10 + 7), (bob.clone(), 7), (carol.clone(), 15)]
+    );
+    assert_eq!(payout.breakdown.proceeds, 101 - 39);
+    assert_eq!(
+        balances(&[&alice, &bob, &carol, &dave]),
+        [before[0] + 79, before[1] + 7, before[2] + 15, before[3] - 101]
+    );
+
+    // Out of escrow, a buyer who mined leaves their cut with the seller just like buying directly
+    let before = balances(&[&alice, &bob, &carol, &dave]);
+
+    client.offer_post(&Offer::AssetSell(carol.clone(), token.clone(), 50), &glyph, &None);
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 50), &None);
+
+    let payout = last_payout();
+
+    assert_eq!(payout.payer, carol);
+    assert_eq!(payout.seller, dave);
+    assert_eq!(
+        payout.breakdown.payouts,
+        map![env, (alice.clone(), 5 + 3), (bob.clone(), 3)]
+    );
+    assert_eq!(
+        balances(&[&alice, &bob, &carol, &dave]),
+        [before[0] + 8, before[1] + 3, before[2] - 50, before[3] + 39]
+    );
+    assert_eq!(fixture.token.balance(&client.address), client.treasury_balance());
+
+    // Too small for any royalty, it all goes to the seller
+    client.offer_post(&glyph, &Offer::Asset(token.clone(), 1), &None);
+    client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 1), &glyph, &None);
+
+    let payout = last_payout();
+
+    assert_eq!(payout.breakdown.payouts, Map::new(env));
+    assert_eq!(payout.breakdown.proceeds, 1);
+    fixture.assert_owner(&hash, &bob);
+}
+
+#[test]
+fn test_royalties_escrow() {
+    // The same sale to a buyer who mined part of the glyph, bought directly or out of escrow
+    let sale = |escrow: bool| {
+        let fixture = Fixture::builder().build();
+        let env = &fixture.env;
+        let client = &fixture.client;
+        let token = fixture.token.address.clone();
+
+        let alice = fixture.user(); // minter and seller
+        let bob = fixture.user(); // buyer
+        let carol = fixture.user();
+
+        client.update(&None, &None, &None, &None, &None, &Some(10), &Some(30));
+
+        client.colors_mine(&bob, &map![env, (0x10, 2)], &None, &Some(alice.clone()), &None);
+        client.colors_mine(&carol, &map![env, (0x20, 2)], &None, &Some(alice.clone()), &None);
+
+        let hash = client
+            .glyph_mint(
+                &alice,
+                &None,
+                &map![
+                    env,
+                    (bob.clone(), map![env, (0x10, vec![env, 0, 1])]),
+                    (carol.clone(), map![env, (0x20, vec![env, 2, 3])])
+                ],
+                &Some(4),
+                &None,
+            )
+            .unwrap();
+
+        let glyph = Offer::Glyph(hash.clone());
+        let before = [&alice, &bob, &carol].map(|user| fixture.token.balance(user));
+
+        if escrow {
+            client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 100), &glyph, &None);
+            client.offer_post(&glyph, &Offer::Asset(token.clone(), 100), &None);
+        } else {
+            client.offer_post(&glyph, &Offer::Asset(token.clone(), 100), &None);
+            client.offer_post(&Offer::AssetSell(bob.clone(), token.clone(), 100), &glyph, &None);
+        }
+
+        fixture.assert_owner(&hash, &bob);
+        assert_eq!(fixture.token.balance(&client.address), client.treasury_balance());
+
+        let after = [&alice, &bob, &carol].map(|user| fixture.token.balance(user));
+
+        [0, 1, 2].map(|i| after[i] - before[i])
+    };
+
+    // Minter 10 and carol's half of the miners' 30, bob's half stays with alice
+    assert_eq!(sale(false), [100 - 15, -100, 15]);
+    assert_eq!(sale(true), sale(false));
+}
@@ -0,0 +1,424 @@
+#![cfg(test)]
+
+extern crate std;
+
+use proptest::prelude::*;
+use soroban_sdk::{testutils::Address as _, Address, BytesN, InvokeError, Map, Vec};
+use std::{vec, vec::Vec as StdVec};
+
+use crate::{
+    storage::persistent::read_color,
+    testutils::Fixture,
+    types::{GlyphType, HashType, MintOptions, Offer, StorageKey},
+};
+
+/* NOTE
+Runs random sequences of color, glyph and offer calls against one contract and checks the invariants after every step
+    Colors are conserved, every (miner, color) ever mined is in someone's balance, a draft or an owned glyph
+    A glyph has at most one owner and shows up in exactly that owner's `glyphs_of`
+    The contract's token balance is exactly what's escrowed in open `AssetOffer`s (plus the treasury)
+Calls are free to fail, a failed call is rolled back, but none of them may fail outside of a contract error
+Small pools of users, colors and amounts keep calls colliding often enough to be interesting
+*/
+
+const USERS: usize = 3;
+const COLORS: u32 = 4;
+const AMOUNTS: [i128; 3] = [10, 25, 60];
+const WIDTH: u32 = 4;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Mine { user: usize, color: u32, amount: u32 },
+    ColorsTransfer { from: usize, to: usize, pick: usize, amount: u32 }, // pick from the sender's held colors
+    Mint { user: usize, draft: u32, pixels: StdVec<(usize, u32)>, finish: bool, paint: bool }, // pick from the minter's held colors, index
+    GlyphTransfer { glyph: usize, to: usize },
+    DraftTransfer { user: usize, draft: usize, to: usize }, // pick from the sender's drafts
+    Scrape { user: usize, glyph: Option<usize>, draft: usize, to: usize }, // a draft is picked when there's no glyph
+    SellForAsset { glyph: usize, amount: usize },
+    BuyWithAsset { user: usize, glyph: usize, amount: usize },
+    Swap { sell: usize, buy: usize },
+    CancelAsset { user: usize, glyph: usize, amount: usize },
+    CancelSells { glyph: usize },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let pixel = (any::<usize>(), 0..WIDTH * WIDTH);
+
+    prop_oneof![
+        4 => (0..USERS, 0..COLORS, 1..5u32).prop_map(|(user, color, amount)| Op::Mine { user, color, amount }),
+        2 => (0..USERS, 0..USERS, any::<usize>(), 1..3u32).prop_map(|(from, to, pick, amount)| {
+            Op::ColorsTransfer { from, to, pick, amount }
+        }),
+        4 => (0..USERS, 0..3u32, prop::collection::vec(pixel, 1..6), prop::bool::weighted(0.75), any::<bool>()).prop_map(
+            |(user, draft, pixels, finish, paint)| Op::Mint { user, draft, pixels, finish, paint }
+        ),
+        1 => (0..8usize, 0..USERS).prop_map(|(glyph, to)| Op::GlyphTransfer { glyph, to }),
+        1 => (0..USERS, any::<usize>(), 0..USERS).prop_map(|(user, draft, to)| Op::DraftTransfer { user, draft, to }),
+        2 => (0..USERS, prop::option::of(0..8usize), any::<usize>(), 0..USERS).prop_map(|(user, glyph, draft, to)| {
+            Op::Scrape { user, glyph, draft, to }
+        }),
+        2 => (0..8usize, 0..AMOUNTS.len()).prop_map(|(glyph, amount)| Op::SellForAsset { glyph, amount }),
+        2 => (0..USERS, 0..8usize, 0..AMOUNTS.len()).prop_map(|(user, glyph, amount)| Op::BuyWithAsset { user, glyph, amount }),
+        1 => (0..8usize, 0..8usize).prop_map(|(sell, buy)| Op::Swap { sell, buy }),
+        1 => (0..USERS, 0..8usize, 0..AMOUNTS.len()).prop_map(|(user, glyph, amount)| Op::CancelAsset { user, glyph, amount }),
+        1 => (0..8usize).prop_map(|glyph| Op::CancelSells { glyph }),
+    ]
+}
+
+struct Model<'a> {
+    fixture: Fixture<'a>,
+    users: StdVec<Address>,
+    glyphs: StdVec<BytesN<32>>, // every hash ever minted, owned or not
+    mined: [[u32; COLORS as usize]; USERS], // miner, color
+}
+
+// Failing is fine, failing with anything other than a contract error isn't
+fn contract_error<T, E: core::fmt::Debug>(op: &Op, result: &Result<T, Result<E, InvokeError>>) {
+    if let Err(Err(error)) = result {
+        panic!("{:?} failed outside of a contract error: {:?}", op, error);
+    }
+}
+
+impl<'a> Model<'a> {
+    fn new() -> Self {
+        let fixture = Fixture::builder().funds(1_000).build();
+        let users = (0..USERS).map(|_| fixture.user()).collect();
+
+        Model {
+            fixture,
+            users,
+            glyphs: StdVec::new(),
+            mined: [[0; COLORS as usize]; USERS],
+        }
+    }
+
+    fn glyph(&self, index: usize) -> Option<BytesN<32>> {
+        if self.glyphs.is_empty() {
+            None
+        } else {
+            Some(self.glyphs[index % self.glyphs.len()].clone())
+        }
+    }
+
+    // owner, miner, color, read straight from storage as the views are too slow to call this often
+    fn balances(&self) -> [[[u32; COLORS as usize]; USERS]; USERS] {
+        let env = &self.fixture.env;
+        let mut balances = [[[0; COLORS as usize]; USERS]; USERS];
+
+        env.as_contract(&self.fixture.client.address, || {
+            for (owner, owner_balances) in balances.iter_mut().enumerate() {
+                for (miner, miner_balances) in owner_balances.iter_mut().enumerate() {
+                    for (color, balance) in miner_balances.iter_mut().enumerate() {
+                        *balance = read_color(env, self.users[miner].clone(), self.users[owner].clone(), color as u32);
+                    }
+                }
+            }
+        });
+
+        balances
+    }
+
+    // Every (miner, color) `user` has a balance of
+    fn held(&self, user: usize) -> StdVec<(usize, u32)> {
+        let balances = self.balances()[user];
+
+        (0..USERS)
+            .flat_map(|miner| (0..COLORS).map(move |color| (miner, color)))
+            .filter(|(miner, color)| balances[*miner][*color as usize] > 0)
+            .collect()
+    }
+
+    fn draft(&self, user: usize, pick: usize) -> Option<u32> {
+        let drafts = self.fixture.client.drafts_of(&self.users[user]);
+
+        if drafts.is_empty() {
+            None
+        } else {
+            drafts.get(pick as u32 % drafts.len())
+        }
+    }
+
+    fn asset(&self, amount: usize) -> (Address, i128) {
+        (self.fixture.token.address.clone(), AMOUNTS[amount])
+    }
+
+    fn apply(&mut self, op: &Op) {
+        let env = &self.fixture.env;
+        let client = &self.fixture.client;
+        let users = &self.users;
+
+        match op.clone() {
+            Op::Mine { user, color, amount } => {
+                let result = client.try_colors_mine(
+                    &users[user],
+                    &Map::from_array(env, [(color, amount)]),
+                    &None,
+                    &None,
+                    &None,
+                );
+
+                contract_error(op, &result);
+
+                if result.is_ok() {
+                    self.mined[user][color as usize] += amount;
+                }
+            }
+            Op::ColorsTransfer { from, to, pick, amount } => {
+                let held = self.held(from);
+
+                if held.is_empty() {
+                    return;
+                }
+
+                let (miner, color) = held[pick % held.len()];
+                let result = client.try_colors_transfer(
+                    &users[from],
+                    &users[to],
+                    &Vec::from_array(env, [(users[miner].clone(), color, amount)]),
+                );
+
+                contract_error(op, &result);
+            }
+            Op::Mint { user, draft, pixels, finish, paint } => {
+                let held = self.held(user);
+
+                if held.is_empty() {
+                    return;
+                }
+
+                let mut colors: Map<Address, Map<u32, Vec<u32>>> = Map::new(env);
+
+                for (pick, index) in pixels {
+                    let (miner, color) = held[pick % held.len()];
+                    let mut miner_colors = colors.get(users[miner].clone()).unwrap_or(Map::new(env));
+                    let mut indexes = miner_colors.get(color).unwrap_or(Vec::new(env));
+
+                    indexes.push_back(index);
+                    miner_colors.set(color, indexes);
+                    colors.set(users[miner].clone(), miner_colors);
+                }
+
+                // Painting over a draft refunds what was there, it never finishes the glyph
+                if paint && !finish {
+                    let result = client.try_draft_paint(&users[user], &draft, &colors);
+
+                    contract_error(op, &result);
+
+                    return;
+                }
+
+                let width = if finish { Some(WIDTH) } else { None };
+                let options = MintOptions {
+                    draft: Some(draft),
+                    ..Default::default()
+                };
+                let result = client.try_glyph_mint(&users[user], &None, &colors, &width, &Some(options));
+
+                contract_error(op, &result);
+
+                if let Ok(Ok(Some(hash))) = result {
+                    if !self.glyphs.contains(&hash) {
+                        self.glyphs.push(hash);
+                    }
+                }
+            }
+            Op::GlyphTransfer { glyph, to } => {
+                if let Some(hash) = self.glyph(glyph) {
+                    let result = client.try_glyph_transfer(&users[to], &HashType::Glyph(hash), &None);
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::DraftTransfer { user, draft, to } => {
+                if let Some(draft) = self.draft(user, draft) {
+                    let result = client.try_glyph_transfer(&users[to], &HashType::Colors(users[user].clone(), draft), &None);
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::Scrape { user, glyph, draft, to } => {
+                let hash_type = match glyph {
+                    Some(glyph) => match self.glyph(glyph) {
+                        Some(hash) => HashType::Glyph(hash),
+                        None => return,
+                    },
+                    None => match self.draft(user, draft) {
+                        Some(draft) => HashType::Colors(users[user].clone(), draft),
+                        None => return,
+                    },
+                };
+                let result = client.try_glyph_scrape(&Some(users[to].clone()), &hash_type, &None, &None);
+
+                contract_error(op, &result);
+            }
+            Op::SellForAsset { glyph, amount } => {
+                if let Some(hash) = self.glyph(glyph) {
+                    let (asset, amount) = self.asset(amount);
+                    let result = client.try_offer_post(&Offer::Glyph(hash), &Offer::Asset(asset, amount), &None);
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::BuyWithAsset { user, glyph, amount } => {
+                if let Some(hash) = self.glyph(glyph) {
+                    let (asset, amount) = self.asset(amount);
+                    let result = client.try_offer_post(
+                        &Offer::AssetSell(users[user].clone(), asset, amount),
+                        &Offer::Glyph(hash),
+                        &None,
+                    );
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::Swap { sell, buy } => {
+                if let (Some(sell), Some(buy)) = (self.glyph(sell), self.glyph(buy)) {
+                    let result = client.try_offer_post(&Offer::Glyph(sell), &Offer::Glyph(buy), &None);
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::CancelAsset { user, glyph, amount } => {
+                if let Some(hash) = self.glyph(glyph) {
+                    let (asset, amount) = self.asset(amount);
+                    let result = client.try_offer_delete(
+                        &Offer::AssetSell(users[user].clone(), asset, amount),
+                        &Some(Offer::Glyph(hash)),
+                        &None,
+                    );
+
+                    contract_error(op, &result);
+                }
+            }
+            Op::CancelSells { glyph } => {
+                if let Some(hash) = self.glyph(glyph) {
+                    let result = client.try_offer_delete(&Offer::Glyph(hash), &None, &None);
+
+                    contract_error(op, &result);
+                }
+            }
+        }
+    }
+
+    fn colors_add(&self, held: &mut [[u32; COLORS as usize]; USERS], colors: Map<Address, Map<u32, Vec<u32>>>) {
+        for (miner, color_indexes) in colors.iter() {
+            let miner = self.users.iter().position(|user| *user == miner).unwrap();
+
+            for (color, indexes) in color_indexes.iter() {
+                held[miner][color as usize] += indexes.len();
+            }
+        }
+    }
+
+    fn check_colors(&self) {
+        let client = &self.fixture.client;
+        let mut held = [[0; COLORS as usize]; USERS];
+
+        for owner_balances in self.balances() {
+            for (miner, miner_balances) in owner_balances.iter().enumerate() {
+                for (color, balance) in miner_balances.iter().enumerate() {
+                    held[miner][color] += balance;
+                }
+            }
+        }
+
+        for owner in self.users.iter() {
+            for draft in client.drafts_of(owner).iter() {
+                match client.glyph_get(&HashType::Colors(owner.clone(), draft)) {
+                    GlyphType::Colors(colors) => self.colors_add(&mut held, colors),
+                    _ => panic!("drafts_of lists a draft that isn't there"),
+                }
+            }
+        }
+
+        for hash in self.glyphs.iter() {
+            if let Ok(Ok(GlyphType::Glyph(glyph))) = client.try_glyph_get(&HashType::Glyph(hash.clone())) {
+                self.colors_add(&mut held, glyph.colors);
+            }
+        }
+
+        assert_eq!(held, self.mined, "colors weren't conserved");
+    }
+
+    fn check_owners(&self) {
+        let client = &self.fixture.client;
+        let listed: StdVec<(Address, BytesN<32>)> = self
+            .users
+            .iter()
+            .flat_map(|user| client.glyphs_of(user, &0, &u32::MAX).iter().map(move |hash| (user.clone(), hash)))
+            .collect();
+
+        for hash in self.glyphs.iter() {
+            let owners: StdVec<&Address> = listed
+                .iter()
+                .filter(|(_, listed_hash)| listed_hash == hash)
+                .map(|(user, _)| user)
+                .collect();
+
+            match client.try_owner_of(hash) {
+                Ok(Ok(owner)) => assert_eq!(owners, [&owner], "glyph owner and glyphs_of disagree"),
+                _ => assert!(owners.is_empty(), "a glyph without an owner is still listed"),
+            }
+        }
+    }
+
+    fn check_escrow(&self) {
+        let env = &self.fixture.env;
+        let client = &self.fixture.client;
+        let token = &self.fixture.token;
+        let mut escrowed = client.treasury_balance();
+
+        for hash in self.glyphs.iter() {
+            for amount in AMOUNTS {
+                let key = StorageKey::AssetOffer(hash.clone(), token.address.clone(), amount);
+                let offers = env.as_contract(&client.address, || {
+                    env.storage().persistent().get::<StorageKey, Vec<Address>>(&key)
+                });
+
+                escrowed += offers.map_or(0, |offers| offers.len() as i128 * amount);
+            }
+        }
+
+        assert_eq!(token.balance(&client.address), escrowed, "escrow doesn't match open asset offers");
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig { cases: 32, ..ProptestConfig::default() })]
+
+    #[test]
+    fn test_invariants(ops in prop::collection::vec(op(), 1..40)) {
+        let mut model = Model::new();
+
+        for op in ops.iter() {
+            model.apply(op);
+            model.check_colors();
+            model.check_owners();
+            model.check_escrow();
+        }
+    }
+}
+
+// Every glyph a scrape pulls apart can be put back together with the same hash
+#[test]
+fn test_scrape_remint() {
+    let mut model = Model::new();
+    let user = model.users[0].clone();
+
+    model.apply(&Op::Mine { user: 0, color: 1, amount: 2 });
+    model.apply(&Op::Mint { user: 0, draft: 0, pixels: std::vec![(0, 0), (0, 5)], finish: true, paint: false });
+
+    let hash = model.glyph(0).unwrap();
+
+    model.apply(&Op::Scrape { user: 0, glyph: Some(0), draft: 0, to: 0 });
+    model.apply(&Op::Mint { user: 0, draft: 0, pixels: std::vec![(0, 0), (0, 5)], finish: true, paint: false });
+
+    assert_eq!(model.glyphs, core::slice::from_ref(&hash));
+    assert_eq!(model.fixture.client.owner_of(&hash), user);
+
+    model.check_colors();
+    model.check_owners();
+
+    // A stranger can't be listed as an owner
+    assert_eq!(model.fixture.client.glyph_count(&Address::generate(&model.fixture.env)), 0);
+}
@@ -0,0 +1,148 @@
+#![cfg(any(test, feature = "testutils"))]
+
+use soroban_sdk::{testutils::Address as _, token, Address, BytesN, Env, Map, Vec};
+
+use crate::contract::{ColorGlyph, ColorGlyphClient};
+
+/* NOTE
+Scenario fixture for driving the contract from Rust tests, enable the `testutils` feature to use it from another crate
+`Fixture::builder().build()` deploys the contract and a SAC token with every auth mocked and an unlimited budget
+Images are rows of 24 bit colors, `BLANK` pixels are left out of the glyph
+Every color in an image is mined by (and paid for by) whoever mints it
+*/
+
+pub const BLANK: u32 = u32::MAX;
+
+pub struct FixtureBuilder {
+    funds: i128,
+}
+
+impl FixtureBuilder {
+    // What every user from `Fixture::user` starts with
+    pub fn funds(mut self, funds: i128) -> Self {
+        self.funds = funds;
+        self
+    }
+
+    pub fn build<'a>(self) -> Fixture<'a> {
+        let env = Env::default();
+
+        env.mock_all_auths();
+        env.budget().reset_unlimited();
+
+        let contract_address = env.register_contract(None, ColorGlyph);
+        let client = ColorGlyphClient::new(&env, &contract_address);
+
+        let token_admin = Address::generate(&env);
+        let token_address = env.register_stellar_asset_contract(token_admin);
+        let token = token::Client::new(&env, &token_address);
+        let token_admin = token::StellarAssetClient::new(&env, &token_address);
+
+        let owner = Address::generate(&env);
+        let fee = Address::generate(&env);
+
+        client.initialize(&owner, &token_address, &fee);
+
+        Fixture {
+            env,
+            client,
+            token,
+            token_admin,
+            funds: self.funds,
+        }
+    }
+}
+
+pub struct Fixture<'a> {
+    pub env: Env,
+    pub client: ColorGlyphClient<'a>,
+    pub token: token::Client<'a>,
+    token_admin: token::StellarAssetClient<'a>,
+    funds: i128,
+}
+
+impl<'a> Fixture<'a> {
+    pub fn builder() -> FixtureBuilder {
+        FixtureBuilder { funds: 10_000 }
+    }
+
+    // A new funded address
+    pub fn user(&self) -> Address {
+        let user = Address::generate(&self.env);
+
+        self.fund(&user, self.funds);
+
+        user
+    }
+
+    fn fund(&self, user: &Address, amount: i128) {
+        self.token_admin.mint(user, &amount);
+    }
+
+    // Mines `amount` of each color for `miner`
+    pub fn mine(&self, miner: &Address, palette: &[u32], amount: u32) {
+        let mut colors: Map<u32, u32> = Map::new(&self.env);
+
+        for color in palette {
+            colors.set(*color, amount);
+        }
+
+        self.client.colors_mine(miner, &colors, &None, &None, &None);
+    }
+
+    // Mines exactly what `image` needs and mints it, returns the glyph hash
+    pub fn mint_image<R: AsRef<[u32]>>(&self, minter: &Address, image: &[R]) -> BytesN<32> {
+        let (amounts, indexes) = self.image_colors(image);
+
+        self.client.colors_mine(minter, &amounts, &None, &None, &None);
+
+        self.client
+            .glyph_mint(
+                minter,
+                &None,
+                &Map::from_array(&self.env, [(minter.clone(), indexes)]),
+                &Some(image_width(image)),
+                &None,
+            )
+            .unwrap()
+    }
+
+    // color: pixel count and color: indexes for `image`
+    pub fn image_colors<R: AsRef<[u32]>>(&self, image: &[R]) -> (Map<u32, u32>, Map<u32, Vec<u32>>) {
+        let width = image_width(image);
+        let mut amounts: Map<u32, u32> = Map::new(&self.env);
+        let mut indexes: Map<u32, Vec<u32>> = Map::new(&self.env);
+
+        for (y, row) in image.iter().enumerate() {
+            for (x, color) in row.as_ref().iter().enumerate() {
+                if *color == BLANK {
+                    continue;
+                }
+
+                let mut color_indexes = indexes.get(*color).unwrap_or(Vec::new(&self.env));
+
+                color_indexes.push_back(y as u32 * width + x as u32);
+                indexes.set(*color, color_indexes);
+                amounts.set(*color, amounts.get(*color).unwrap_or(0) + 1);
+            }
+        }
+
+        (amounts, indexes)
+    }
+
+    pub fn assert_owner(&self, hash: &BytesN<32>, owner: &Address) {
+        assert_eq!(self.client.owner_of(hash), *owner);
+    }
+
+    pub fn assert_balance(&self, user: &Address, amount: i128) {
+        assert_eq!(self.token.balance(user), amount);
+    }
+
+    pub fn assert_colors(&self, owner: &Address, miner: &Address, color: u32, amount: u32) {
+        assert_eq!(self.client.color_balance(owner, &color, &Some(miner.clone())), amount);
+    }
+}
+
+fn image_width<R: AsRef<[u32]>>(image: &[R]) -> u32 {
+    image.first().map_or(0, |row| row.as_ref().len() as u32)
+}
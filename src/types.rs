@@ -1,4 +1,4 @@
-use soroban_sdk::{contracterror, contracttype, Address, BytesN, Map, Vec};
+use soroban_sdk::{contracterror, contracttype, Address, BytesN, Map, String, Vec};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -13,6 +13,27 @@ pub enum Error {
     MissingAddress = 7,
     MissingBuy = 8,
     NotInitialized = 9,
+    InsufficientColors = 10,
+    IndexOutOfRange = 11,
+    DuplicateIndex = 12,
+    InvalidWidth = 13,
+    MaxPriceExceeded = 14,
+    AlreadyInitialized = 15,
+    GlyphOwned = 16,        // the glyph is already minted and owned (not scraped)
+    ColorsPending = 17,     // no longer returned, scrapes go into their own draft
+    OfferExists = 18,
+    OfferNotFound = 19,
+    SelfTrade = 20,
+    RoyaltyOverflow = 21,   // royalties can't be computed or add up to more than the sale amount
+    PaymentCapReached = 22, // a sale would need more payments than `MAX_SALE_PAYMENTS`
+    InvalidPrice = 23,
+    InvalidRate = 24,
+    InvalidAmount = 25,
+    InvalidExpiration = 26,
+    InvalidCanvas = 27,
+    UriTooLong = 28,
+    MetadataTooLarge = 29,
+    MetadataFrozen = 30,
 }
 
 #[contracttype]
@@ -26,18 +47,46 @@ pub enum StorageKey {
     MinterRoyaltyRate,
     MinerRoyaltyRate,
     Color(Address, Address, u32),
-    Colors(Address),
+    Colors(Address), // legacy, the one build per owner from before drafts, only read by `draft_recover_legacy`
+    Draft(Address, u32), // owner, draft id
+    Drafts(Address),
     Glyph(BytesN<32>),
     GlyphOwner(BytesN<32>),
     GlyphMinter(BytesN<32>),
     GlyphOffer(BytesN<32>),
+    GlyphOfferSpender(BytesN<32>, Offer), // hash, buy offer, who posted it when it wasn't the owner
     AssetOffer(BytesN<32>, Address, i128),
+    OwnerGlyph(Address, u32), // owner, position
+    OwnerGlyphCount(Address),
+    OwnerGlyphPosition(BytesN<32>), // where the glyph sits in its owner's list
+    ColorInventory(Address, u32), // owner, position
+    ColorInventoryLength(Address),
+    ColorInventoryPosition(Address, Address, u32), // owner, miner, color
+    ColorTotal(Address, u32), // owner, color, summed over miners
+    ColorAllowance(Address, Address, Address, u32),
+    GlyphApproval(BytesN<32>),
+    GlyphOperator(Address, Address),
+    BaseUri,
+    MinePrice,
+    PriceCurve,
+    Canvas,
+    ColorSupply(u32),
+    ColorBurned(u32),
+    ColorCost(Address, u32), // miner, color, what was paid for the units still around
+    TreasuryBalance,
+    BurnRefundRate,
+    GlyphMetadata(BytesN<32>),
+    GlyphMetadataPolicy(BytesN<32>),
+    GlyphTransparent(BytesN<32>),
+    GlyphHistory(BytesN<32>, u32), // hash, index
+    GlyphHistoryLength(BytesN<32>),
+    RemintPolicy,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum HashType {
-    Colors(Address), // means you can only be building or scraping one glyph at a time
+    Colors(Address, u32), // owner, draft id, an owner can be building or scraping any number of drafts at once
     Glyph(BytesN<32>),
 }
 
@@ -48,6 +97,40 @@ pub enum GlyphType {
     Glyph(Glyph),
 }
 
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlyphStatus {
+    Free,
+    Owned(Address),  // owner, minting it now fails with `GlyphOwned`
+    Minted(Address), // minter as the `RemintPolicy` left it, scraped so it can be minted again
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphPreview {
+    pub hash: BytesN<32>,
+    pub length: u32, // up to the last colored pixel, same as `Glyph::length`
+    pub pixels: u32, // colored pixels
+    pub status: GlyphStatus,
+}
+
+// What's left of a scrape, all zeros and no draft once it's done
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrapeProgress {
+    pub draft: Option<u32>, // where the rest is waiting, scrape `HashType::Colors(owner, draft)` to continue
+    pub miners: u32,
+    pub colors: u32, // (miner, color) entries, each scrape call moves up to its limit of these
+    pub pixels: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+    pub max_width: u32,
+    pub max_height: u32,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct Glyph {
@@ -56,6 +139,93 @@ pub struct Glyph {
     pub colors: Map<Address, Map<u32, Vec<u32>>>,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphMetadata {
+    pub title: String,
+    pub story: String,
+    pub external_url: String,
+    pub attributes: Map<String, String>,
+}
+
+// Everything `glyph_mint` takes past the pixels, `None` mints an opaque glyph without metadata out of draft 0
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MintOptions {
+    pub metadata: MintMetadata, // only with a width
+    pub transparent: bool,      // only with a width
+    pub draft: Option<u32>,
+}
+
+// Stands in for `Option<GlyphMetadata>` which can't be a contracttype field
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum MintMetadata {
+    #[default]
+    None,
+    Some(GlyphMetadata),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MetadataPolicy {
+    Minter,
+    Owner,
+    FrozenOnSale, // editable by the owner until the glyph's first sale through an offer
+    Frozen,
+}
+
+// Who takes the minter role (royalties and `MetadataPolicy::Minter` edits) when a scraped glyph is minted again
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RemintPolicy {
+    OriginalMinter, // the first minter keeps it for good
+    LatestMinter,   // whoever re-mints takes it over
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Provenance {
+    Mint(Address, Address),            // minter, owner
+    Remint(Address, Address, Address), // re-minter, owner, minter from here on under the `RemintPolicy` at the time
+    Transfer(Address, Address),        // from, to
+    Sale(Address, Address, Offer),     // seller, buyer, price (the `Asset` paid or the glyph swapped for it)
+    Scrape(Address),                   // owner
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProvenanceEntry {
+    pub ledger: u32,
+    pub record: Provenance,
+}
+
+// How an asset sale was split up, see royalties.rs
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoyaltyBreakdown {
+    pub price: i128,
+    pub payouts: Map<Address, i128>, // minter and miners, summed where they overlap
+    pub proceeds: i128,              // the seller's share after royalties
+}
+
+// Shorthands for `glyph_mint_spans`, all bounds are inclusive
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PixelSpan {
+    Index(u32),
+    Range(u32, u32),     // start index, end index
+    Run(u32, u32),       // start index, length
+    Row(u32, u32, u32),  // row, start column, end column (needs the glyph width)
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderFormat {
+    Svg,
+    Bmp,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub enum OfferCreate {
@@ -70,3 +240,34 @@ pub enum Offer {
     Asset(Address, i128), // BLOCKED once tuples support Option use that instead of AssetSell
     AssetSell(Address, Address, i128), // asset owner, sac, amount
 }
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Approval {
+    pub address: Address,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allowance {
+    pub amount: u32,
+    pub expiration_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinePrice {
+    pub base_price: i128,                  // per pixel in the configured token
+    pub color_multipliers: Map<u32, u32>,  // color: percent of the base price (100 == 1x)
+    pub volume_discounts: Vec<(u32, u32)>, // (minimum pixels, percent off) sorted by minimum pixels
+}
+
+// Rates are 7 decimal fixed point (10_000_000 == 1.0) relative to the color's scheduled price
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PriceCurve {
+    Flat,
+    Linear(i128),      // price grows by `rate` of the scheduled price for every unit of the color ever mined
+    Exponential(i128), // price compounds by `rate` for every unit of the color ever mined
+}